@@ -6,7 +6,7 @@ Place 8 queens on a chess board such that they can not attack each other.
 
 extern crate quickbacktrack;
 
-use quickbacktrack::{BackTrackSolver, Puzzle, SolveSettings};
+use quickbacktrack::{BackTrackSolver, Grid2D, LocalSearchSolver, Puzzle, SolveSettings, TreeRecorder, Variables};
 
 #[derive(Clone)]
 pub struct EightQueens {
@@ -61,6 +61,25 @@ impl Puzzle for EightQueens {
     }
 }
 
+impl Grid2D for EightQueens {
+    fn grid_size(&self) -> [usize; 2] {[self.queens.len(); 2]}
+
+    fn cell(&self, pos: [usize; 2]) -> char {
+        if self.queens[pos[1]] as usize == pos[0] + 1 {'x'} else {'.'}
+    }
+
+    fn grid_pos(&self, pos: usize) -> Option<[usize; 2]> {
+        let q = self.queens[pos] as usize;
+        if q > 0 {Some([q - 1, pos])} else {None}
+    }
+}
+
+impl Variables for EightQueens {
+    fn unassigned(&self) -> Vec<usize> {
+        (0..self.queens.len()).filter(|&i| self.queens[i] == 0).collect()
+    }
+}
+
 impl EightQueens {
     pub fn new(size: usize) -> EightQueens {
         EightQueens {
@@ -124,6 +143,25 @@ impl EightQueens {
         return self.find_min_pos();
     }
 
+    pub fn conflicts(&self, pos: usize) -> usize {
+        let q = self.queens[pos];
+        if q == 0 { return 0; }
+        let this_pos = [q as i8 - 1, pos as i8];
+        let mut n = 0;
+        for (i, q2) in self.queens.iter().enumerate() {
+            if i == pos || *q2 == 0 { continue; }
+            let that_pos = [*q2 as i8 - 1, i as i8];
+            if *q2 == q || can_take(that_pos, this_pos) {
+                n += 1;
+            }
+        }
+        return n;
+    }
+
+    pub fn all_values(&self, _pos: usize) -> Vec<u8> {
+        (1..(self.queens.len() + 1) as u8).collect()
+    }
+
     pub fn possible(&self, pos: usize) -> Vec<u8> {
         let mut res = vec![];
         if self.queens[pos] > 0 {
@@ -165,4 +203,62 @@ fn main() {
             }
         }
     }
+
+    // Local search starts with a queen in every row, all on the same diagonal,
+    // and moves queens until none can take another.
+    for &i in &[8, 100] {
+        let max_iterations = 100_000;
+        let board = EightQueens { queens: (1..(i + 1) as u8).collect() };
+        let settings = SolveSettings::new()
+            .max_iterations(max_iterations)
+        ;
+        let positions: Vec<usize> = (0..i).collect();
+        let solver = LocalSearchSolver::new(board, settings);
+        match solver.solve(&positions, |board, p| board.conflicts(p),
+                           |board, p| board.all_values(p)) {
+            None => {
+                println!("local search {} >{}", i, max_iterations);
+            }
+            Some(x) => {
+                println!("local search {} {}", i, x.iterations);
+            }
+        }
+    }
+
+    // Random solutions, picking among the rows with fewest possible queens at random.
+    for seed in 0..3 {
+        let solver = BackTrackSolver::new(EightQueens::new(8), SolveSettings::new());
+        let solution = solver.random_solution_ties(seed,
+            |board, p| board.possible(p).len(), |board, p| board.possible(p))
+            .expect("Expected solution");
+        println!("random {:?}", solution.puzzle.queens);
+    }
+
+    // Estimate the size of the search tree for different ways of picking the next row.
+    let solver = BackTrackSolver::new(EightQueens::new(8), SolveSettings::new());
+    let first = solver.estimate_tree_size(1000, 0,
+        |board| board.queens.iter().position(|&q| q == 0), |board, p| board.possible(p));
+    let min = solver.estimate_tree_size(1000, 0, |board| board.find_min_pos(), |board, p| board.possible(p));
+    println!("tree size first row {:.0} ± {:.0}", first.mean, first.std_error());
+    println!("tree size min row {:.0} ± {:.0}", min.mean, min.std_error());
+
+    // Record the search tree for a small board.
+    // Render it with `cargo run --example eight_queens | sed -n '/^digraph/,$p' | dot -Tsvg > tree.svg`.
+    let print_tree = false;
+    if print_tree {
+        let board = EightQueens::new(6);
+        let mut tree = TreeRecorder::new().max_nodes(1000);
+        let solver = BackTrackSolver::new(board, SolveSettings::new());
+        solver.solve_with_hooks(|board| board.find_min_pos(),
+                                |board, p| board.possible(p), &mut tree);
+        print!("{}", tree.to_dot());
+    }
+
+    // Watch the solver live with `cargo run --example eight_queens --features viewer`.
+    #[cfg(feature = "viewer")]
+    {
+        let solver = BackTrackSolver::new(EightQueens::new(8), SolveSettings::new());
+        solver.solve_with_hooks(|board| board.find_min_pos(),
+                                |board, p| board.possible(p), &mut quickbacktrack::Viewer::new());
+    }
 }
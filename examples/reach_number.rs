@@ -0,0 +1,138 @@
+/*
+
+Starting with the number 1, find the shortest sequence of moves that reaches a target number.
+Each move either adds 3, doubles the number, or subtracts 1.
+
+The sequence of moves has no natural length, so depth first search would keep
+adding moves forever. Iterative deepening tries all sequences of 0 moves, then 1 move,
+and so on, which finds the shortest sequence.
+
+*/
+
+extern crate quickbacktrack;
+
+use quickbacktrack::{BackTrackSolver, Puzzle, SolveOutcome, SolveSettings, Trace};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Move {
+    Add3,
+    Double,
+    Sub1,
+}
+
+impl Move {
+    pub fn parse(text: &str) -> Option<Option<Move>> {
+        match text {
+            "Some(Add3)" => Some(Some(Move::Add3)),
+            "Some(Double)" => Some(Some(Move::Double)),
+            "Some(Sub1)" => Some(Some(Move::Sub1)),
+            "None" => Some(None),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, n: i64) -> i64 {
+        match self {
+            Move::Add3 => n + 3,
+            Move::Double => n * 2,
+            Move::Sub1 => n - 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ReachNumber {
+    pub moves: Vec<Option<Move>>,
+    pub target: i64,
+}
+
+impl ReachNumber {
+    pub fn new(target: i64) -> ReachNumber {
+        ReachNumber {moves: vec![], target}
+    }
+
+    pub fn value(&self) -> i64 {
+        self.moves.iter().flatten().fold(1, |n, m| m.apply(n))
+    }
+
+    pub fn next_move(&self) -> Option<usize> {
+        Some(self.moves.iter().take_while(|m| m.is_some()).count())
+    }
+
+    pub fn possible(&self, _ind: usize) -> Vec<Option<Move>> {
+        // The last move is tried first.
+        vec![Some(Move::Sub1), Some(Move::Add3), Some(Move::Double)]
+    }
+}
+
+impl Puzzle for ReachNumber {
+    type Pos = usize;
+    type Val = Option<Move>;
+
+    fn set(&mut self, ind: usize, val: Option<Move>) {
+        if ind >= self.moves.len() {
+            self.moves.resize(ind + 1, None);
+        }
+        self.moves[ind] = val;
+        while let Some(None) = self.moves.last() {
+            self.moves.pop();
+        }
+    }
+
+    fn get(&self, ind: usize) -> Option<Move> {
+        self.moves.get(ind).cloned().unwrap_or(None)
+    }
+
+    fn print(&self) {
+        println!("{:?} = {}", self.moves, self.value());
+    }
+
+    fn is_solved(&self) -> bool {
+        self.value() == self.target
+    }
+
+    fn remove(&mut self, _other: &ReachNumber) {}
+}
+
+fn main() {
+    for target in [2, 10, 37, 100] {
+        let settings = SolveSettings::new()
+            .debug(false)
+        ;
+        let solver = BackTrackSolver::new(ReachNumber::new(target), settings);
+        match solver.iterative_deepening(ReachNumber::next_move, ReachNumber::possible, &mut ()) {
+            SolveOutcome::Solved(solution) => {
+                solution.puzzle.print();
+                println!("moves: {}, iterations: {}", solution.puzzle.moves.len(), solution.iterations);
+            }
+            _ => println!("No solution for {}", target),
+        }
+    }
+
+    // With a maximum depth, a failure does not prove that there is no solution.
+    let settings = SolveSettings::new()
+        .max_depth(3)
+    ;
+    let solver = BackTrackSolver::new(ReachNumber::new(100), settings);
+    match solver.solve_outcome(ReachNumber::next_move, ReachNumber::possible, &mut ()) {
+        SolveOutcome::DepthLimited => println!("No solution for 100 within 3 moves"),
+        _ => unreachable!(),
+    }
+
+    // Record the search as text, then read it back and replay it.
+    let mut trace = Trace::new();
+    let solver = BackTrackSolver::new(ReachNumber::new(10), SolveSettings::new());
+    solver.iterative_deepening(ReachNumber::next_move, ReachNumber::possible, &mut trace);
+    let text = trace.to_text();
+    let trace: Trace<usize, Option<Move>> = Trace::parse(&text, |s| s.parse().ok(), Move::parse)
+        .expect("Expected valid trace");
+    let mut state = ReachNumber::new(10);
+    trace.replay(&mut state, ReachNumber::possible, |_, _| true).expect("Expected replay to match");
+    println!("Replayed {} events to {}", trace.len(), state.value());
+
+    // A second run makes the same steps.
+    let mut again = Trace::new();
+    let solver = BackTrackSolver::new(ReachNumber::new(10), SolveSettings::new());
+    solver.iterative_deepening(ReachNumber::next_move, ReachNumber::possible, &mut again);
+    assert_eq!(trace.first_difference(&again), None);
+}
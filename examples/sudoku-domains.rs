@@ -0,0 +1,185 @@
+/*
+
+Sudoku example using candidate domains.
+
+Instead of computing possible values from scratch for every slot,
+the candidates are updated incrementally each time a value is set.
+This makes it possible to use the generic heuristics from the library.
+
+*/
+
+extern crate quickbacktrack;
+
+use std::sync::Arc;
+
+use quickbacktrack::{select, BackTrackSolver, BitDomains, Domains, Puzzle, SolveSettings, Variables};
+use quickbacktrack::propagate::Domain;
+
+#[derive(Clone)]
+pub struct Sudoku {
+	pub slots: [[u8; 9]; 9],
+	pub domains: BitDomains,
+}
+
+impl Sudoku {
+	pub fn new(slots: [[u8; 9]; 9]) -> Sudoku {
+		let mut peers = vec![vec![]; 81];
+		for y in 0..9 {
+			for x in 0..9 {
+				for y2 in 0..9 {
+					for x2 in 0..9 {
+						if (x, y) == (x2, y2) {continue};
+						if x == x2 || y == y2 || (x / 3 == x2 / 3 && y / 3 == y2 / 3) {
+							peers[y * 9 + x].push(y2 * 9 + x2);
+						}
+					}
+				}
+			}
+		}
+
+		let mut res = Sudoku {
+			slots: [[0; 9]; 9],
+			domains: BitDomains::with_peers(vec![Domain::range(1, 10); 81], Arc::new(peers)),
+		};
+		for (y, row) in slots.iter().enumerate() {
+			for (x, &v) in row.iter().enumerate() {
+				res.set([x, y], v);
+			}
+		}
+		res
+	}
+}
+
+impl Variables for Sudoku {
+	fn unassigned(&self) -> Vec<[usize; 2]> {
+		let mut res = vec![];
+		for y in 0..9 {
+			for x in 0..9 {
+				if self.slots[y][x] == 0 {
+					res.push([x, y]);
+				}
+			}
+		}
+		res
+	}
+
+	fn neighbors(&self, pos: [usize; 2]) -> Vec<[usize; 2]> {
+		self.domains.peers(pos[1] * 9 + pos[0]).iter().map(|&i| [i % 9, i / 9]).collect()
+	}
+}
+
+impl Domains for Sudoku {
+	fn candidates(&self, pos: [usize; 2]) -> Vec<u8> {
+		if self.slots[pos[1]][pos[0]] != 0 {return vec![]};
+		self.domains.get(pos[1] * 9 + pos[0]).iter().rev().map(|v| v as u8).collect()
+	}
+
+	fn domain_size(&self, pos: [usize; 2]) -> usize {
+		if self.slots[pos[1]][pos[0]] != 0 {return 0};
+		self.domains.get(pos[1] * 9 + pos[0]).len()
+	}
+}
+
+impl Puzzle for Sudoku {
+	type Pos = [usize; 2];
+	type Val = u8;
+
+	fn solve_simple<F: FnMut(&mut Self, Self::Pos, Self::Val)>(&mut self, mut f: F) {
+		loop {
+			let mut found_any = false;
+			for y in 0..9 {
+				for x in 0..9 {
+					if self.slots[y][x] != 0 { continue; }
+					if let Some(v) = self.domains.get(y * 9 + x).value() {
+						f(self, [x, y], v as u8);
+						found_any = true;
+					}
+				}
+			}
+			if !found_any { break; }
+		}
+	}
+
+	fn set(&mut self, pos: [usize; 2], val: u8) {
+		let old = self.slots[pos[1]][pos[0]];
+		self.slots[pos[1]][pos[0]] = val;
+		let to_val = |v: u8| if v == 0 {None} else {Some(v as u32)};
+		self.domains.assign(pos[1] * 9 + pos[0], to_val(old), to_val(val));
+	}
+
+	fn get(&self, pos: [usize; 2]) -> u8 {
+		self.slots[pos[1]][pos[0]]
+	}
+
+	fn remove(&mut self, other: &Sudoku) {
+		for y in 0..9 {
+			for x in 0..9 {
+				if other.slots[y][x] != 0 {
+					self.set([x, y], 0);
+				}
+			}
+		}
+	}
+
+	fn print(&self) {
+		println!(" ___ ___ ___");
+		for y in 0..9 {
+			print!("|");
+			for x in 0..9 {
+				let v = self.slots[y][x];
+				if v == 0 {
+					print!(" ");
+				} else {
+					print!("{}", self.slots[y][x]);
+				}
+				if x % 3 == 2 {
+					print!("|");
+				}
+			}
+			println!();
+			if y % 3 == 2 {
+				println!(" ---+---+---");
+			}
+		}
+	}
+
+	fn is_solved(&self) -> bool {
+		for y in 0..9 {
+			for x in 0..9 {
+				if self.slots[y][x] == 0 { return false; }
+			}
+		}
+		true
+	}
+}
+
+fn main() {
+	let x = Sudoku::new([
+		[0, 2, 0, 0, 0, 0, 0, 0, 0],
+		[0, 0, 0, 0, 0, 4, 5, 0, 0],
+		[0, 6, 0, 0, 0, 0, 0, 0, 0],
+
+		[0, 0, 4, 0, 0, 0, 0, 0, 0],
+		[9, 0, 3, 0, 1, 0, 0, 7, 0],
+		[0, 0, 0, 0, 0, 0, 0, 0, 0],
+
+		[0, 0, 0, 0, 0, 0, 0, 0, 0],
+		[0, 8, 0, 0, 0, 0, 3, 0, 0],
+		[0, 0, 0, 1, 0, 0, 0, 9, 0],
+	]);
+	x.print();
+
+	let settings = SolveSettings::new()
+		.solve_simple(true)
+		.debug(false)
+		.difference(true)
+	;
+
+	let solver = BackTrackSolver::new(x, settings);
+	let solution = solver.solve(select::min_remaining_values, Sudoku::candidates)
+		.expect("Expected solution");
+
+	println!("Difference:");
+	solution.puzzle.print();
+	println!("Non-trivial moves: {}", solution.iterations);
+}
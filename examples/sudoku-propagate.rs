@@ -0,0 +1,96 @@
+/*
+
+Sudoku example using constraint propagation.
+
+Instead of writing a custom puzzle, the rules are modeled as all-different constraints
+on rows, columns and groups. The domains are reduced by propagation between guesses,
+and restored by the solver when backtracking.
+
+*/
+
+extern crate quickbacktrack;
+
+use quickbacktrack::{BackTrackSolver, Rater, SolveSettings, Strategy};
+use quickbacktrack::propagate::{AllDifferent, Csp, Domain, Propagation};
+
+pub fn sudoku(slots: [[u8; 9]; 9]) -> Csp {
+	let mut propagation = Propagation::new();
+	for i in 0..9 {
+		propagation.add(AllDifferent::new((0..9).map(|x| i * 9 + x).collect()));
+		propagation.add(AllDifferent::new((0..9).map(|y| y * 9 + i).collect()));
+		let (bx, by) = (3 * (i % 3), 3 * (i / 3));
+		propagation.add(AllDifferent::new((0..9).map(|j| (by + j / 3) * 9 + bx + j % 3).collect()));
+	}
+
+	let mut domains = vec![];
+	for row in &slots {
+		for &v in row {
+			domains.push(match v {
+				0 => Domain::range(1, 10),
+				v => Domain::singleton(v as u32),
+			});
+		}
+	}
+	Csp::new(domains, propagation)
+}
+
+pub fn print(csp: &Csp) {
+	println!(" ___ ___ ___");
+	for y in 0..9 {
+		print!("|");
+		for x in 0..9 {
+			match csp.value(y * 9 + x) {
+				None => print!(" "),
+				Some(v) => print!("{}", v),
+			}
+			if x % 3 == 2 {
+				print!("|");
+			}
+		}
+		println!();
+		if y % 3 == 2 {
+			println!(" ---+---+---");
+		}
+	}
+}
+
+fn main() {
+	let x = sudoku([
+		[0, 2, 0, 0, 0, 0, 0, 0, 0],
+		[0, 0, 0, 0, 0, 4, 5, 0, 0],
+		[0, 6, 0, 0, 0, 0, 0, 0, 0],
+
+		[0, 0, 4, 0, 0, 0, 0, 0, 0],
+		[9, 0, 3, 0, 1, 0, 0, 7, 0],
+		[0, 0, 0, 0, 0, 0, 0, 0, 0],
+
+		[0, 0, 0, 0, 0, 0, 0, 0, 0],
+		[0, 8, 0, 0, 0, 0, 3, 0, 0],
+		[0, 0, 0, 1, 0, 0, 0, 9, 0],
+	]);
+	print(&x);
+
+	let settings = SolveSettings::new()
+		.solve_simple(true)
+		.debug(false)
+	;
+
+	let solver = BackTrackSolver::new(x.clone(), settings);
+	let solution = solver.solve(Csp::select_min, Csp::values)
+		.expect("Expected solution");
+
+	println!("Solution:");
+	print(&solution.puzzle);
+	println!("Non-trivial moves: {}", solution.iterations);
+
+	// Rate the puzzle, counting how often the propagators removed values.
+	let rater = Rater::new(SolveSettings::new())
+		.rule_weight("all different", 0.1)
+	;
+	let strategies: Vec<Strategy<Csp>> = vec![
+		(Csp::select_min, Csp::values),
+	];
+	let rating = rater.rate_with_rules(&x, &strategies, |csp| csp.propagation.fired())
+		.expect("Expected solution");
+	println!("Rating: {:.2} {:?}, rules: {:?}", rating.score, rating.tier, rating.measurements.rules);
+}
@@ -11,7 +11,10 @@ for picking the next empty slot.
 
 extern crate quickbacktrack;
 
-use quickbacktrack::{combine, BackTrackSolver, MultiBackTrackSolver, Puzzle, SolveSettings};
+use std::fmt;
+
+use quickbacktrack::generate::{self, Generator};
+use quickbacktrack::{combine, BackTrackSolver, Grid2D, MultiBackTrackSolver, Puzzle, Rater, SolveSettings, Strategy, Tier};
 
 #[derive(Clone)]
 pub struct Sudoku {
@@ -58,25 +61,32 @@ impl Puzzle for Sudoku {
 	}
 
 	fn print(&self) {
-		println!(" ___ ___ ___");
+		let mut text = String::new();
+		self.write_to(&mut text).unwrap();
+		print!("{}", text);
+	}
+
+	fn write_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+		writeln!(w, " ___ ___ ___")?;
 		for y in 0..9 {
-			print!("|");
+			write!(w, "|")?;
 			for x in 0..9 {
 				let v = self.slots[y][x];
 				if v == 0 {
-					print!(" ");
+					write!(w, " ")?;
 				} else {
-					print!("{}", self.slots[y][x]);
+					write!(w, "{}", self.slots[y][x])?;
 				}
 				if x % 3 == 2 {
-					print!("|");
+					write!(w, "|")?;
 				}
 			}
-			println!("");
+			writeln!(w)?;
 			if y % 3 == 2 {
-				println!(" ---+---+---");
+				writeln!(w, " ---+---+---")?;
 			}
 		}
+		Ok(())
 	}
 
 	fn is_solved(&self) -> bool {
@@ -89,6 +99,19 @@ impl Puzzle for Sudoku {
 	}
 }
 
+impl Grid2D for Sudoku {
+	fn grid_size(&self) -> [usize; 2] {[9, 9]}
+
+	fn cell(&self, pos: [usize; 2]) -> char {
+		match self.get(pos) {
+			0 => '.',
+			v => (b'0' + v) as char,
+		}
+	}
+
+	fn grid_pos(&self, pos: [usize; 2]) -> Option<[usize; 2]> {Some(pos)}
+}
+
 impl Sudoku {
 
 	pub fn find_empty(&self) -> Option<[usize; 2]> {
@@ -390,6 +413,49 @@ fn main() {
 	solution.puzzle.print();
 	println!("Non-trivial moves: {}", solution.iterations);
 	println!("Strategy: {}", solution.strategy.unwrap_or(0));
+
+	// Rate the examples by difficulty.
+	// The default weights of the rater are calibrated by `tests/rating.rs`.
+	let rater = Rater::new(SolveSettings::new());
+	let strategies: Vec<Strategy<Sudoku>> = vec![
+		(Sudoku::find_min_empty, Sudoku::possible),
+		(Sudoku::find_min_potential, Sudoku::possible),
+	];
+	let examples = [
+		example1(), example2(), example3(), example4(), example5(),
+		example6(), example7(), example8(), example9(), example10(),
+	];
+	for (i, x) in examples.iter().enumerate() {
+		let rating = rater.rate(x, &strategies).expect("Expected solution");
+		println!("Example {}: {:.2} {:?}, guesses: {}, depth: {}, simple: {:.0}%", i + 1,
+			rating.score, rating.tier, rating.measurements.guesses, rating.measurements.max_depth,
+			rating.measurements.simple_share() * 100.0);
+	}
+
+	// Generate a new puzzle with a unique solution, removing values in symmetric pairs.
+	// Start from a random complete grid.
+	let solved = BackTrackSolver::new(Sudoku {slots: [[0; 9]; 9]}, SolveSettings::new())
+		.random_solution(7, Sudoku::find_min_empty, Sudoku::possible)
+		.expect("Expected solution").puzzle;
+	let positions: Vec<[usize; 2]> = (0..81).map(|i| [i % 9, i / 9]).collect();
+	let generator = Generator::new(SolveSettings::new())
+		.seed(7)
+		.symmetry(generate::rotational([9, 9]))
+		.target(Rater::new(SolveSettings::new()), Tier::Hard)
+	;
+	let puzzle = generator.generate(&solved, &positions, 0, strategies[0]);
+	let givens = positions.iter().filter(|&&p| puzzle.get(p) != 0).count();
+	let rating = rater.rate(&puzzle, &strategies).expect("Expected solution");
+	println!("Generated:");
+	puzzle.print();
+	println!("Givens: {}, rating: {:.2} {:?}", givens, rating.score, rating.tier);
+
+	// Watch the solver live with `cargo run --example sudoku --features viewer`.
+	#[cfg(feature = "viewer")]
+	{
+		let solver = BackTrackSolver::new(example10(), SolveSettings::new().solve_simple(true));
+		solver.solve_with_hooks(Sudoku::find_min_empty, Sudoku::possible, &mut quickbacktrack::Viewer::new());
+	}
 }
 
 /*
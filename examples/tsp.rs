@@ -16,7 +16,7 @@ The algorithm is exact, and therefore not efficient.
 extern crate quickbacktrack;
 
 use std::sync::Arc;
-use quickbacktrack::{BackTrackSolver, Puzzle, SolveSettings};
+use quickbacktrack::{BackTrackSolver, BestFirstSolver, Puzzle, SolveSettings};
 
 #[derive(Clone, Debug)]
 pub struct Tsp {
@@ -237,6 +237,30 @@ impl Tsp {
         sum / 2.0
     }
 
+    /// Computes lower bound of the distance not yet counted.
+    /// Roads to cities with a pair of roads are already counted.
+    /// Roads between two cities without a pair of roads are counted by both,
+    /// so therefore we divide by 2.
+    pub fn remaining_lower_bound(&self) -> f64 {
+        let mut sum = 0.0;
+        for s in 0..self.slots.len() {
+            if self.slots[s].is_some() { continue; }
+
+            let mut min_dist: Option<f64> = None;
+            for p in self.possible(s) {
+                let (i, j) = p.unwrap();
+                let mut dist = 0.0;
+                if self.slots[i].is_none() { dist += self.distances[s][i]; }
+                if self.slots[j].is_none() { dist += self.distances[s][j]; }
+                if min_dist.is_none() || min_dist.unwrap() > dist {
+                    min_dist = Some(dist);
+                }
+            }
+            sum += min_dist.unwrap_or(0.0);
+        }
+        sum / 2.0
+    }
+
     pub fn upper_bound(&self) -> f64 {
         let mut sum = 0.0;
         for s in 0..self.slots.len() {
@@ -323,4 +347,25 @@ fn main() {
 		.expect("Expected solution").puzzle;
 	println!("Difference:");
 	difference.print();
+
+	// Best-first search finds the shortest route,
+	// using the distance so far and a lower bound of the remaining distance.
+	let x = Tsp::new_2d(&vec![
+			vec![0, 0, 0, 0, 1, 0, 0, 0, 0, 1],
+			vec![0, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+			vec![0, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+			vec![1, 0, 1, 0, 0, 1, 0, 0, 0, 0],
+			vec![0, 0, 0, 0, 0, 0, 1, 0, 0, 0],
+			vec![0, 1, 0, 1, 0, 0, 0, 0, 0, 1],
+		]);
+	let settings = SolveSettings::new()
+		.solve_simple(false)
+	;
+	let solver = BestFirstSolver::new(x, settings);
+	let solution = solver.solve(|s| s.find_min_empty(), |s, p| s.possible(p),
+		|s| s.distance(), |s| s.remaining_lower_bound())
+		.expect("Expected solution");
+	println!("Shortest:");
+	solution.puzzle.print();
+	println!("Iterations: {}", solution.iterations);
 }
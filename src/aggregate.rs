@@ -0,0 +1,366 @@
+//! Rank aggregation for combining strategies.
+
+use std::hash::Hash;
+
+use fnv::FnvHashMap;
+
+use crate::Puzzle;
+
+/// The method used to aggregate priority lists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregation {
+    /// Sums the weighted index of each item (Borda count).
+    Borda,
+    /// Sums the weighted reciprocal rank `1 / (k + rank)`,
+    /// where rank is 1 for the item with highest priority in a list.
+    ///
+    /// This favors items that have high priority in some list.
+    ReciprocalRank {
+        /// A constant that reduces the influence of the top ranks, usually 60.
+        k: f64,
+    },
+    /// Starts with the Borda order and swaps neighbors while this
+    /// reduces the weighted number of pairwise disagreements with the lists.
+    ///
+    /// This is a local approximation of the Kemeny ranking.
+    Kemeny,
+    /// Uses the best weighted rank of each item in any list.
+    ///
+    /// The rank of an item is divided by the weight of the list.
+    MinRank,
+}
+
+/// Decides the order of items with equal priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Keeps the order in which items are seen first.
+    FirstSeen,
+    /// Reverses the order in which items are seen first,
+    /// giving highest priority to the item seen first.
+    LastSeen,
+}
+
+/// Stores settings for combining priority lists.
+///
+/// Default settings:
+///
+/// - weights: `[]` (every list has weight 1)
+/// - aggregation: `Aggregation::Borda`
+/// - tie_break: `TieBreak::FirstSeen`
+#[derive(Clone, Debug)]
+pub struct CombineSettings {
+    /// The weight of each list. Missing weights are 1.
+    ///
+    /// Weights must be finite, since infinite or NaN scores can not be ordered.
+    pub weights: Vec<f64>,
+    /// The aggregation method.
+    pub aggregation: Aggregation,
+    /// How to order items with equal priority.
+    pub tie_break: TieBreak,
+}
+
+impl Default for CombineSettings {
+    fn default() -> CombineSettings {
+        CombineSettings::new()
+    }
+}
+
+impl CombineSettings {
+    /// Creates new combine settings.
+    pub fn new() -> CombineSettings {
+        CombineSettings {
+            weights: vec![],
+            aggregation: Aggregation::Borda,
+            tie_break: TieBreak::FirstSeen,
+        }
+    }
+
+    /// Sets the weight of each list.
+    ///
+    /// Panics if a weight is not finite.
+    pub fn set_weights(&mut self, val: Vec<f64>) {
+        for (i, w) in val.iter().enumerate() {
+            assert!(w.is_finite(), "Weight {} of list {} is not finite", w, i);
+        }
+        self.weights = val;
+    }
+
+    /// The weight of each list.
+    pub fn weights(mut self, val: Vec<f64>) -> Self {
+        self.set_weights(val);
+        self
+    }
+
+    /// Sets the aggregation method.
+    pub fn set_aggregation(&mut self, val: Aggregation) {
+        self.aggregation = val;
+    }
+
+    /// The aggregation method.
+    pub fn aggregation(mut self, val: Aggregation) -> Self {
+        self.set_aggregation(val);
+        self
+    }
+
+    /// Sets how to order items with equal priority.
+    pub fn set_tie_break(&mut self, val: TieBreak) {
+        self.tie_break = val;
+    }
+
+    /// How to order items with equal priority.
+    pub fn tie_break(mut self, val: TieBreak) -> Self {
+        self.set_tie_break(val);
+        self
+    }
+
+    /// Gets the weight of a list.
+    ///
+    /// Panics if the weight is not finite.
+    pub fn weight(&self, list: usize) -> f64 {
+        let w = self.weights.get(list).cloned().unwrap_or(1.0);
+        assert!(w.is_finite(), "Weight {} of list {} is not finite", w, list);
+        w
+    }
+}
+
+/// Combines multiple priority lists together, using settings.
+///
+/// Like the lists, the result has the item with highest priority last.
+/// The order is the same for every run, since ties are broken deterministically.
+pub fn combine_with<T>(lists: Vec<Vec<T>>, settings: &CombineSettings) -> Vec<T>
+    where T: Clone + Hash + Eq
+{
+    // Assign ids in the order items are seen first.
+    let mut ids: FnvHashMap<T, usize> = FnvHashMap::default();
+    let mut items: Vec<T> = vec![];
+    let lists: Vec<Vec<usize>> = lists.into_iter().map(|list| {
+        list.into_iter().map(|item| {
+            *ids.entry(item.clone()).or_insert_with(|| {
+                items.push(item);
+                items.len() - 1
+            })
+        }).collect()
+    }).collect();
+    let order = aggregate(&lists, items.len(), settings);
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order.into_iter().map(|i| items[i].take().unwrap()).collect()
+}
+
+/// Combines multiple priority lists together, using settings and comparing items with `PartialEq`.
+///
+/// This works like `combine_with`, but does not require `Hash`.
+pub fn combine_eq_with<T>(lists: Vec<Vec<T>>, settings: &CombineSettings) -> Vec<T>
+    where T: PartialEq
+{
+    let mut items: Vec<T> = vec![];
+    let lists: Vec<Vec<usize>> = lists.into_iter().map(|list| {
+        list.into_iter().map(|item| {
+            match items.iter().position(|x| *x == item) {
+                Some(id) => id,
+                None => {
+                    items.push(item);
+                    items.len() - 1
+                }
+            }
+        }).collect()
+    }).collect();
+    let order = aggregate(&lists, items.len(), settings);
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order.into_iter().map(|i| items[i].take().unwrap()).collect()
+}
+
+/// Aggregates lists of ids from `0` to `n`, returning ids in order of increasing priority.
+fn aggregate(lists: &[Vec<usize>], n: usize, settings: &CombineSettings) -> Vec<usize> {
+    let mut scores = vec![0.0; n];
+    match settings.aggregation {
+        Aggregation::Borda | Aggregation::Kemeny => {
+            for (l, list) in lists.iter().enumerate() {
+                let w = settings.weight(l);
+                for (i, &id) in list.iter().enumerate() {
+                    scores[id] += w * i as f64;
+                }
+            }
+        }
+        Aggregation::ReciprocalRank {k} => {
+            for (l, list) in lists.iter().enumerate() {
+                let w = settings.weight(l);
+                for (i, &id) in list.iter().enumerate() {
+                    let rank = (list.len() - i) as f64;
+                    scores[id] += w / (k + rank);
+                }
+            }
+        }
+        Aggregation::MinRank => {
+            let mut best = vec![f64::INFINITY; n];
+            for (l, list) in lists.iter().enumerate() {
+                let w = settings.weight(l);
+                for (i, &id) in list.iter().enumerate() {
+                    let rank = (list.len() - i) as f64 / w;
+                    if rank < best[id] {best[id] = rank};
+                }
+            }
+            // Lower rank means higher priority.
+            for id in 0..n {scores[id] = -best[id]};
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    if settings.tie_break == TieBreak::LastSeen {order.reverse()};
+    // Stable sort keeps the tie break order.
+    order.sort_by(|&a, &b| scores[a].partial_cmp(&scores[b]).unwrap_or(::std::cmp::Ordering::Equal));
+
+    if settings.aggregation == Aggregation::Kemeny {
+        kemeny(lists, n, settings, &mut order);
+    }
+    order
+}
+
+fn kemeny(lists: &[Vec<usize>], n: usize, settings: &CombineSettings, order: &mut [usize]) {
+    // `prefer[a * n + b]` is the weight of lists where `a` has higher priority than `b`.
+    let mut prefer = vec![0.0; n * n];
+    for (l, list) in lists.iter().enumerate() {
+        let w = settings.weight(l);
+        for (i, &a) in list.iter().enumerate() {
+            for &b in &list[..i] {
+                prefer[a * n + b] += w;
+            }
+        }
+    }
+    loop {
+        let mut swapped = false;
+        for j in 0..order.len().saturating_sub(1) {
+            let (a, b) = (order[j], order[j + 1]);
+            // `b` is after `a`, so it has higher priority.
+            if prefer[a * n + b] > prefer[b * n + a] {
+                order.swap(j, j + 1);
+                swapped = true;
+            }
+        }
+        if !swapped {break}
+    }
+}
+
+/// Combines multiple strategies into a new one.
+///
+/// The position is picked by weighted vote among the strategies,
+/// breaking ties by the order of strategies.
+/// If any strategy finds no position, the combined strategy finds no position either.
+///
+/// The possible values are combined using `combine_with`.
+#[allow(clippy::type_complexity)]
+pub fn combine_strategies<T, F, G>(
+    strategies: Vec<(F, G)>,
+    settings: CombineSettings
+) -> (impl FnMut(&T) -> Option<T::Pos>, impl FnMut(&T, T::Pos) -> Vec<T::Val>)
+    where T: Puzzle,
+          T::Pos: PartialEq,
+          T::Val: Hash + Eq,
+          F: FnMut(&T) -> Option<T::Pos>,
+          G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    let (mut fs, mut gs): (Vec<F>, Vec<G>) = strategies.into_iter().unzip();
+    let weights = settings.clone();
+    let f = move |state: &T| {
+        let mut votes: Vec<(T::Pos, f64)> = vec![];
+        for (i, f) in fs.iter_mut().enumerate() {
+            let pos = f(state)?;
+            match votes.iter_mut().find(|v| v.0 == pos) {
+                None => votes.push((pos, weights.weight(i))),
+                Some(v) => v.1 += weights.weight(i),
+            }
+        }
+        let mut max: Option<(T::Pos, f64)> = None;
+        for (pos, w) in votes {
+            if max.is_none_or(|(_, m)| m < w) {
+                max = Some((pos, w));
+            }
+        }
+        max.map(|(pos, _)| pos)
+    };
+    let g = move |state: &T, pos: T::Pos| {
+        let lists = gs.iter_mut().map(|g| g(state, pos)).collect();
+        combine_with(lists, &settings)
+    };
+    (f, g)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists() -> Vec<Vec<char>> {
+        ["bcda", "abdc", "adbc"].iter().map(|l| l.chars().collect()).collect()
+    }
+
+    fn combine(settings: CombineSettings) -> String {
+        let res = combine_with(lists(), &settings).into_iter().collect::<String>();
+        let eq = combine_eq_with(lists(), &settings).into_iter().collect::<String>();
+        assert_eq!(res, eq);
+        res
+    }
+
+    #[test]
+    fn aggregations() {
+        for &(aggregation, expected) in &[
+            (Aggregation::Borda, "badc"),
+            (Aggregation::ReciprocalRank {k: 0.0}, "bdac"),
+            (Aggregation::ReciprocalRank {k: 60.0}, "badc"),
+            (Aggregation::Kemeny, "abdc"),
+            (Aggregation::MinRank, "bdca"),
+        ] {
+            assert_eq!(combine(CombineSettings::new().aggregation(aggregation)), expected,
+                "{:?}", aggregation);
+        }
+    }
+
+    #[test]
+    fn weights() {
+        // The first list outweighs the others.
+        for &(aggregation, expected) in &[
+            (Aggregation::Borda, "bcda"),
+            (Aggregation::ReciprocalRank {k: 0.0}, "bdca"),
+            (Aggregation::Kemeny, "bcda"),
+            (Aggregation::MinRank, "bcda"),
+        ] {
+            let settings = CombineSettings::new().aggregation(aggregation).weights(vec![3.0, 1.0]);
+            assert_eq!(combine(settings), expected, "{:?}", aggregation);
+        }
+    }
+
+    #[test]
+    fn tie_break() {
+        for &(aggregation, expected) in &[
+            (Aggregation::Borda, "abdc"),
+            (Aggregation::ReciprocalRank {k: 0.0}, "bdac"),
+            (Aggregation::Kemeny, "abdc"),
+            (Aggregation::MinRank, "dbac"),
+        ] {
+            let settings = CombineSettings::new().aggregation(aggregation).tie_break(TieBreak::LastSeen);
+            assert_eq!(combine(settings), expected, "{:?}", aggregation);
+        }
+    }
+
+    #[test]
+    fn ties_are_deterministic() {
+        let lists = vec![vec!['a', 'b'], vec!['b', 'a']];
+        for _ in 0..10 {
+            assert_eq!(combine_with(lists.clone(), &CombineSettings::new()), vec!['a', 'b']);
+            let last_seen = CombineSettings::new().tie_break(TieBreak::LastSeen);
+            assert_eq!(combine_with(lists.clone(), &last_seen), vec!['b', 'a']);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Weight NaN of list 1 is not finite")]
+    fn nan_weight() {
+        CombineSettings::new().weights(vec![1.0, f64::NAN]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weight inf of list 0 is not finite")]
+    fn infinite_weight() {
+        let mut settings = CombineSettings::new();
+        settings.weights = vec![f64::INFINITY];
+        combine_with(lists(), &settings);
+    }
+}
@@ -0,0 +1,184 @@
+//! Beam search.
+
+use std::cmp::Ordering;
+
+use crate::progress::ProgressClock;
+use crate::{Puzzle, SearchStats, Solution, SolveSettings};
+
+/// Solves puzzles using beam search.
+///
+/// Instead of backtracking, the solver keeps the best partial states at each depth,
+/// using a closure to score states. Every state in the beam is expanded by setting
+/// each possible value at the position picked by the first closure,
+/// and only the `width` children with highest score are kept for the next depth.
+///
+/// This trades completeness for speed: the memory and time used per depth is bounded
+/// by the width, but the solver might miss a solution if the score is misleading.
+/// A width of 1 is a greedy search, and a very large width is breadth first search.
+pub struct BeamSolver<T> where T: Puzzle {
+    /// Stores the original state.
+    pub original: T,
+    /// The number of states to keep at each depth.
+    pub width: usize,
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+}
+
+impl<T> BeamSolver<T> where T: Puzzle {
+    /// Creates a new solver, keeping `width` states at each depth.
+    pub fn new(puzzle: T, width: usize, settings: SolveSettings) -> BeamSolver<T> {
+        BeamSolver {
+            original: puzzle,
+            width,
+            settings,
+        }
+    }
+
+    /// Sets the number of states to keep at each depth.
+    pub fn set_width(&mut self, val: usize) {
+        self.width = val;
+    }
+
+    /// The number of states to keep at each depth.
+    pub fn width(mut self, val: usize) -> Self {
+        self.set_width(val);
+        self
+    }
+
+    /// Solves puzzle, using a closure to look for best position to set a value next,
+    /// a closure for picking options in preferred order, and a closure for scoring states.
+    ///
+    /// States with higher score are kept in the beam.
+    /// When states have equal score, the one expanded from a better parent is kept,
+    /// and among values at the same position, the last one in the list is kept.
+    ///
+    /// Each child state counts as one iteration.
+    /// `SolveSettings::max_iterations` limits the number of iterations,
+    /// and `SolveSettings::max_depth` limits the number of depths.
+    pub fn solve<F, G, S>(self, mut f: F, mut g: G, mut score: S) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              S: FnMut(&T) -> f64
+    {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        let mut start = self.original.clone();
+        if self.settings.solve_simple {
+            start.solve_simple(|state, pos, val| state.set(pos, val));
+        }
+        if start.is_solved() {
+            return Some(self.solution(start, iterations));
+        }
+        let mut beam = vec![start];
+        let mut depth = 0;
+        while !beam.is_empty() {
+            if self.settings.max_depth.is_some_and(|max| depth >= max) {
+                if self.settings.debug {
+                    self.settings.trace(format_args!("Max depth reached"));
+                }
+                return None;
+            }
+            depth += 1;
+
+            let mut children: Vec<(f64, T)> = vec![];
+            for state in &beam {
+                let pos = match f(state) {
+                    None => continue,
+                    Some(x) => x,
+                };
+                // Expand the preferred value first, such that it wins ties.
+                for val in g(state, pos).into_iter().rev() {
+                    let mut child = state.clone();
+                    child.set(pos, val);
+                    if self.settings.solve_simple {
+                        child.solve_simple(|state, pos, val| state.set(pos, val));
+                    }
+                    iterations += 1;
+                    if self.settings.max_iterations.is_some_and(|max| iterations > max) {
+                        return None;
+                    }
+                    if child.is_solved() {
+                        if self.settings.debug {
+                            self.settings.trace(format_args!("Solved! Iterations: {}", iterations));
+                        }
+                        return Some(self.solution(child, iterations));
+                    }
+                    self.settings.report_progress(&mut clock, iterations, || (depth, None));
+                    children.push((score(&child), child));
+                }
+            }
+
+            // Stable sort keeps the expansion order for equal scores.
+            children.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+            children.truncate(self.width);
+            beam = children.into_iter().map(|(_, state)| state).collect();
+
+            if self.settings.debug {
+                if let Some(ms) = self.settings.sleep_ms {
+                    sleep(Duration::from_millis(ms));
+                }
+                self.settings.trace(format_args!("Depth: {} beam: {} it: {}", depth, beam.len(), iterations));
+                if let Some(best) = beam.first() {
+                    self.settings.trace_state(best);
+                }
+            }
+        }
+        if self.settings.debug {
+            self.settings.trace(format_args!("No more possible choices"));
+        }
+        None
+    }
+
+    fn solution(&self, mut state: T, iterations: u64) -> Solution<T> {
+        if self.settings.difference {
+            state.remove(&self.original);
+        }
+        Solution {puzzle: state, iterations, strategy: None, stats: SearchStats::default()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{Queens, Row};
+
+    fn queens(n: usize, width: usize, settings: SolveSettings) -> Option<Solution<Queens>> {
+        BeamSolver::new(Queens::new(n), width, settings).solve(Queens::first_empty, Queens::values, Queens::free)
+    }
+
+    #[test]
+    fn solves_queens() {
+        let solution = queens(8, 3, SolveSettings::new()).unwrap();
+        assert!(solution.puzzle.is_valid());
+    }
+
+    #[test]
+    fn greedy_fails() {
+        // Keeping the state with most free cells leads into a dead end.
+        assert!(queens(8, 1, SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn limits() {
+        let iterations = queens(8, 3, SolveSettings::new()).unwrap().iterations;
+        assert!(queens(8, 3, SolveSettings::new().max_iterations(iterations - 1)).is_none());
+        assert!(queens(8, 3, SolveSettings::new().max_iterations(iterations)).is_some());
+        assert!(queens(8, 3, SolveSettings::new().max_depth(7)).is_none());
+        assert!(queens(8, 3, SolveSettings::new().max_depth(8)).is_some());
+    }
+
+    #[test]
+    fn ties_keep_expansion_order() {
+        let solve = |score: fn(&Row) -> f64| BeamSolver::new(Row::new(3, 3, 5), 1, SolveSettings::new())
+            .solve(Row::first_empty, Row::values, score).unwrap();
+        // With equal scores, the preferred value is kept, which is the smallest.
+        let solution = solve(|_| 0.0);
+        assert_eq!(solution.puzzle.cells, vec![1, 1, 3]);
+        assert_eq!(solution.iterations, 9);
+        // A higher score wins over the preferred value.
+        assert_eq!(solve(|row| row.total() as f64).puzzle.cells, vec![3, 1, 1]);
+    }
+}
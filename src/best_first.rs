@@ -0,0 +1,187 @@
+//! Best-first search.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::progress::ProgressClock;
+use crate::{Puzzle, SearchStats, Solution, SolveSettings};
+
+/// Stores a partial state in the priority queue.
+struct Entry<T> {
+    /// The cost so far plus the heuristic.
+    estimate: f64,
+    /// The heuristic.
+    remaining: f64,
+    /// Increases with every state pushed.
+    order: u64,
+    state: T,
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    // The greatest entry is popped first, so lower estimates compare as greater.
+    // Ties are broken by lowest heuristic, then by the latest state.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+            .then(other.remaining.total_cmp(&self.remaining))
+            .then(self.order.cmp(&other.order))
+    }
+}
+
+/// Solves puzzles using best-first search (A*).
+///
+/// The solver keeps a priority queue of partial states, ordered by the cost so far
+/// plus a heuristic estimate of the remaining cost. The partial state with lowest estimate
+/// is expanded by setting each possible value at the position picked by the first closure.
+///
+/// When the heuristic is admissible, which means that it never overestimates the remaining cost,
+/// the first solved state taken from the queue has the lowest cost of all solutions.
+/// A heuristic that is always zero gives uniform cost search.
+///
+/// Every partial state in the queue is a clone of the puzzle,
+/// so memory use grows with the number of states that are not expanded yet.
+pub struct BestFirstSolver<T> where T: Puzzle {
+    /// Stores the original state.
+    pub original: T,
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+}
+
+impl<T> BestFirstSolver<T> where T: Puzzle {
+    /// Creates a new solver.
+    pub fn new(puzzle: T, settings: SolveSettings) -> BestFirstSolver<T> {
+        BestFirstSolver {
+            original: puzzle,
+            settings,
+        }
+    }
+
+    /// Solves puzzle, using a closure to look for best position to set a value next,
+    /// a closure for picking options, a closure computing the cost so far,
+    /// and a closure estimating the remaining cost.
+    ///
+    /// The order of values only matters for states with equal estimates,
+    /// where the last value in the list is expanded first.
+    ///
+    /// Each expanded state counts as one iteration.
+    /// `SolveSettings::max_iterations` limits the number of iterations,
+    /// and `SolveSettings::max_depth` limits the number of values set after the original state.
+    pub fn solve<F, G, C, H>(self, mut f: F, mut g: G, mut cost: C, mut heuristic: H) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              C: FnMut(&T) -> f64,
+              H: FnMut(&T) -> f64
+    {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        let mut order: u64 = 0;
+        let mut queue: BinaryHeap<Entry<(T, usize)>> = BinaryHeap::new();
+        let mut start = self.original.clone();
+        if self.settings.solve_simple {
+            start.solve_simple(|state, pos, val| state.set(pos, val));
+        }
+        let remaining = heuristic(&start);
+        queue.push(Entry {estimate: cost(&start) + remaining, remaining, order, state: (start, 0)});
+
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        while let Some(Entry {estimate, state: (state, depth), ..}) = queue.pop() {
+            if self.settings.debug {
+                if let Some(ms) = self.settings.sleep_ms {
+                    sleep(Duration::from_millis(ms));
+                }
+                self.settings.trace(format_args!("Estimate: {} depth: {} queue: {} it: {}",
+                    estimate, depth, queue.len(), iterations));
+                self.settings.trace_state(&state);
+            }
+            if state.is_solved() {
+                if self.settings.debug {
+                    self.settings.trace(format_args!("Solved! Iterations: {}", iterations));
+                }
+                let mut puzzle = state;
+                if self.settings.difference {
+                    puzzle.remove(&self.original);
+                }
+                return Some(Solution {puzzle, iterations, strategy: None, stats: SearchStats::default()});
+            }
+            iterations += 1;
+            if self.settings.max_iterations.is_some_and(|max| iterations > max) {
+                return None;
+            }
+            self.settings.report_progress(&mut clock, iterations, || (depth, None));
+            if self.settings.max_depth.is_some_and(|max| depth >= max) {continue};
+
+            let pos = match f(&state) {
+                None => continue,
+                Some(x) => x,
+            };
+            for val in g(&state, pos) {
+                let mut child = state.clone();
+                child.set(pos, val);
+                if self.settings.solve_simple {
+                    child.solve_simple(|state, pos, val| state.set(pos, val));
+                }
+                let remaining = heuristic(&child);
+                order += 1;
+                queue.push(Entry {
+                    estimate: cost(&child) + remaining,
+                    remaining,
+                    order,
+                    state: (child, depth + 1),
+                });
+            }
+        }
+        if self.settings.debug {
+            self.settings.trace(format_args!("No more possible choices"));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+
+    const WEIGHTS: [f64; 4] = [4.0, 1.0, 3.0, 2.0];
+
+    fn cost(row: &Row) -> f64 {
+        row.cells.iter().zip(&WEIGHTS).map(|(&v, w)| v as f64 * w).sum()
+    }
+
+    /// Every empty cell has at least value 1.
+    fn heuristic(row: &Row) -> f64 {
+        row.cells.iter().zip(&WEIGHTS).filter(|&(&v, _)| v == 0).map(|(_, w)| w).sum()
+    }
+
+    #[test]
+    fn finds_optimum() {
+        let puzzle = Row::new(4, 3, 8);
+        let all = puzzle.solutions(SolveSettings::new(), &mut ()).0;
+        let optimum = all.iter().map(|cells| cost(&Row {cells: cells.clone(), ..puzzle.clone()}))
+            .fold(f64::INFINITY, f64::min);
+        // Depth first search finds another solution first.
+        assert!(cost(&Row {cells: all[0].clone(), ..puzzle.clone()}) > optimum);
+
+        for h in [heuristic as fn(&Row) -> f64, |_: &Row| 0.0] {
+            let solution = BestFirstSolver::new(puzzle.clone(), SolveSettings::new())
+                .solve(Row::first_empty, Row::values, cost, h).unwrap();
+            assert_eq!(cost(&solution.puzzle), optimum);
+            assert_eq!(solution.puzzle.cells, vec![1, 3, 1, 3]);
+        }
+    }
+}
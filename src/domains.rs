@@ -0,0 +1,208 @@
+//! Candidate values at positions.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::Puzzle;
+use crate::propagate::{Csp, Domain};
+
+/// Implemented by puzzles that keep track of candidate values at each position.
+///
+/// This lets generic heuristics look at the candidates without knowing
+/// the internal structure of the puzzle.
+pub trait Domains: Puzzle {
+    /// Returns the candidate values at a position.
+    ///
+    /// The last value in the list has highest priority, like the closure used by solvers,
+    /// so this method can be passed directly to `BackTrackSolver::solve`.
+    fn candidates(&self, pos: Self::Pos) -> Vec<Self::Val>;
+    /// Returns the number of candidate values at a position.
+    fn domain_size(&self, pos: Self::Pos) -> usize {
+        self.candidates(pos).len()
+    }
+}
+
+impl Domains for Csp {
+    fn candidates(&self, pos: usize) -> Vec<Domain> {
+        self.values(pos)
+    }
+
+    fn domain_size(&self, pos: usize) -> usize {
+        self.domains[pos].len()
+    }
+}
+
+/// Stores candidate values for positions indexed by `usize`, as bits.
+///
+/// Each value at a position has a count of how many times it is eliminated.
+/// A value is a candidate when its count is zero, so eliminations can be undone
+/// in any order. This makes it cheap to update the candidates in `Puzzle::set`,
+/// including when the solver restores old values while backtracking.
+///
+/// When peers are given, assigning a value at a position eliminates it from the peers,
+/// which is how all-different constraints like rows in Sudoku work.
+///
+/// The domains can be stored inside a puzzle and updated with `assign`,
+/// or solved on their own as a puzzle where `None` means no value,
+/// e.g. to color a graph where peers are the edges.
+#[derive(Clone)]
+pub struct BitDomains {
+    initial: Arc<Vec<Domain>>,
+    peers: Arc<Vec<Vec<usize>>>,
+    domains: Vec<Domain>,
+    counts: Vec<u32>,
+    values: Vec<Option<u32>>,
+    width: usize,
+}
+
+impl BitDomains {
+    /// Creates new domains from the initial candidates, without peers.
+    pub fn new(initial: Vec<Domain>) -> BitDomains {
+        BitDomains::with_peers(initial, Arc::new(vec![]))
+    }
+
+    /// Creates new domains from the initial candidates,
+    /// where assigning a value eliminates it from the peers of a position.
+    pub fn with_peers(initial: Vec<Domain>, peers: Arc<Vec<Vec<usize>>>) -> BitDomains {
+        let width = initial.iter().filter_map(|d| d.max()).max().map(|m| m as usize + 1).unwrap_or(0);
+        BitDomains {
+            counts: vec![0; initial.len() * width],
+            values: vec![None; initial.len()],
+            domains: initial.clone(),
+            initial: Arc::new(initial),
+            peers,
+            width,
+        }
+    }
+
+    /// Returns the number of positions.
+    pub fn len(&self) -> usize {self.domains.len()}
+
+    /// Returns `true` if there are no positions.
+    pub fn is_empty(&self) -> bool {self.domains.is_empty()}
+
+    /// Gets the candidates at a position.
+    pub fn get(&self, pos: usize) -> Domain {self.domains[pos]}
+
+    /// Returns the peers of a position.
+    pub fn peers(&self, pos: usize) -> &[usize] {
+        self.peers.get(pos).map(|p| &p[..]).unwrap_or(&[])
+    }
+
+    /// Eliminates a candidate value at a position.
+    pub fn eliminate(&mut self, pos: usize, val: u32) {
+        if val as usize >= self.width {return};
+        let c = &mut self.counts[pos * self.width + val as usize];
+        *c += 1;
+        if *c == 1 {self.domains[pos].remove(val)};
+    }
+
+    /// Undoes an elimination of a candidate value at a position.
+    pub fn restore(&mut self, pos: usize, val: u32) {
+        if val as usize >= self.width {return};
+        let c = &mut self.counts[pos * self.width + val as usize];
+        debug_assert!(*c > 0, "Restored value {} at position {} that is not eliminated", val, pos);
+        *c -= 1;
+        if *c == 0 && self.initial[pos].contains(val) {self.domains[pos].insert(val)};
+    }
+
+    /// Updates the candidates of peers when the value at a position changes.
+    ///
+    /// Call this from `Puzzle::set`, using `None` for no value.
+    pub fn assign(&mut self, pos: usize, old: Option<u32>, new: Option<u32>) {
+        if old == new {return};
+        let peers = self.peers.clone();
+        if let Some(peers) = peers.get(pos) {
+            for &p in peers {
+                if let Some(v) = old {self.restore(p, v)};
+                if let Some(v) = new {self.eliminate(p, v)};
+            }
+        }
+    }
+}
+
+impl Puzzle for BitDomains {
+    type Pos = usize;
+    type Val = Option<u32>;
+
+    fn set(&mut self, pos: usize, val: Option<u32>) {
+        let old = self.values[pos];
+        self.values[pos] = val;
+        self.assign(pos, old, val);
+    }
+
+    fn get(&self, pos: usize) -> Option<u32> {self.values[pos]}
+
+    fn print(&self) {
+        println!("{:?}", self.values);
+    }
+
+    fn is_solved(&self) -> bool {
+        self.values.iter().all(|v| v.is_some())
+    }
+
+    fn remove(&mut self, other: &BitDomains) {
+        for pos in 0..self.values.len() {
+            if other.values[pos].is_some() {self.set(pos, None)};
+        }
+    }
+
+    fn write_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(w, "{:?}", self.values)
+    }
+}
+
+impl Domains for BitDomains {
+    /// Returns the candidates at a position without a value, with the smallest value last.
+    fn candidates(&self, pos: usize) -> Vec<Option<u32>> {
+        if self.values[pos].is_some() {return vec![]};
+        self.domains[pos].iter().rev().map(Some).collect()
+    }
+
+    fn domain_size(&self, pos: usize) -> usize {
+        if self.values[pos].is_some() {0} else {self.domains[pos].len()}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{select, BackTrackSolver, SolveSettings};
+
+    #[test]
+    fn many_eliminations() {
+        let mut domains = BitDomains::new(vec![Domain::range(0, 2)]);
+        for _ in 0..300 {domains.eliminate(0, 1)};
+        for _ in 0..299 {domains.restore(0, 1)};
+        assert_eq!(domains.get(0).value(), Some(0));
+        domains.restore(0, 1);
+        assert_eq!(domains.get(0).len(), 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Restored value 1 at position 0 that is not eliminated")]
+    fn restore_without_eliminate() {
+        BitDomains::new(vec![Domain::range(0, 2)]).restore(0, 1);
+    }
+
+    #[test]
+    fn color_graph() {
+        // A square with one diagonal needs three colors.
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)];
+        let mut peers = vec![vec![]; 4];
+        for &(a, b) in &edges {
+            peers[a].push(b);
+            peers[b].push(a);
+        }
+        let colors = |n| BitDomains::with_peers(vec![Domain::range(0, n); 4], Arc::new(peers.clone()));
+        let solve = |domains| BackTrackSolver::new(domains, SolveSettings::new())
+            .solve(select::min_remaining_values, BitDomains::candidates);
+        assert!(solve(colors(2)).is_none());
+        let solution = solve(colors(3)).unwrap().puzzle;
+        for &(a, b) in &edges {
+            assert_ne!(Puzzle::get(&solution, a), Puzzle::get(&solution, b));
+        }
+        assert_eq!(Puzzle::get(&solution, 1), Puzzle::get(&solution, 3));
+    }
+}
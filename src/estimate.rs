@@ -0,0 +1,108 @@
+//! Search tree size estimation.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{Puzzle, SolveSettings};
+
+/// Stores an estimate of the number of nodes in a search tree.
+#[derive(Clone, Copy, Debug)]
+pub struct TreeEstimate {
+    /// The mean of the estimates from each probe.
+    pub mean: f64,
+    /// The sample variance of the estimates from each probe.
+    pub variance: f64,
+    /// The number of probes.
+    pub samples: usize,
+}
+
+impl TreeEstimate {
+    /// Returns the standard error of the mean.
+    ///
+    /// The estimates are often spread over many orders of magnitude,
+    /// so a large standard error means that more samples are needed.
+    pub fn std_error(&self) -> f64 {
+        if self.samples == 0 {0.0} else {(self.variance / self.samples as f64).sqrt()}
+    }
+}
+
+/// Estimates the size of the search tree using random probes.
+///
+/// Each probe walks from the original state to a leaf, picking a random value at each choice.
+/// When the choices have branching factors `b1, b2, ...`, the probe estimates
+/// the tree size as `1 + b1 + b1 * b2 + ...` (Knuth's estimator).
+pub(crate) fn estimate_tree_size<T, F, G>(
+    original: &T,
+    settings: &SolveSettings,
+    samples: usize,
+    seed: u64,
+    mut f: F,
+    mut g: G
+) -> TreeEstimate
+    where T: Puzzle,
+          F: FnMut(&T) -> Option<T::Pos>,
+          G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut estimates = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let mut state = original.clone();
+        let mut estimate = 1.0;
+        let mut width = 1.0;
+        let mut depth = 0;
+        loop {
+            if settings.solve_simple {
+                state.solve_simple(|state, pos, val| state.set(pos, val));
+            }
+            if state.is_solved() || settings.max_depth.is_some_and(|max| depth >= max) {break};
+            let pos = match f(&state) {
+                None => break,
+                Some(x) => x,
+            };
+            let possible = g(&state, pos);
+            if possible.is_empty() {break};
+            width *= possible.len() as f64;
+            estimate += width;
+            state.set(pos, possible[rng.random_range(0..possible.len())]);
+            depth += 1;
+        }
+        estimates.push(estimate);
+    }
+
+    let n = estimates.len() as f64;
+    let mean = if samples == 0 {0.0} else {estimates.iter().sum::<f64>() / n};
+    let variance = if samples < 2 {0.0} else {
+        estimates.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0)
+    };
+    TreeEstimate {mean, variance, samples}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+
+    fn estimate(puzzle: &Row, seed: u64) -> TreeEstimate {
+        estimate_tree_size(puzzle, &SolveSettings::new(), 100, seed, Row::first_empty, Row::values)
+    }
+
+    #[test]
+    fn full_binary_tree() {
+        // Every cell takes 1 or 2 and the sum is never reached,
+        // so the tree is a full binary tree of depth `n`.
+        for n in 0..8 {
+            let estimate = estimate(&Row::new(n, 2, 2 * n as u32 + 1), 3);
+            assert_eq!(estimate.mean, (1u64 << (n + 1)) as f64 - 1.0);
+            assert_eq!(estimate.variance, 0.0);
+        }
+    }
+
+    #[test]
+    fn seed() {
+        // Larger values leave fewer choices, so probes differ by the values picked.
+        let puzzle = Row::new(5, 3, 9);
+        assert_eq!(estimate(&puzzle, 1).mean, estimate(&puzzle, 1).mean);
+        assert!(estimate(&puzzle, 1).variance > 0.0);
+        assert_ne!(estimate(&puzzle, 1).mean, estimate(&puzzle, 2).mean);
+    }
+}
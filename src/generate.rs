@@ -0,0 +1,164 @@
+//! Puzzle generation by removing values from a solved puzzle.
+//!
+//! A `Generator` starts with a solved puzzle and removes values one position at a time,
+//! keeping each removal only if the puzzle still has exactly one solution.
+//! Positions are visited in random order, optionally together with a symmetric position,
+//! and a `Rater` can limit the difficulty of the result.
+//!
+//! The difficulty is only limited from above: removals make puzzles harder,
+//! but the generator stops when no removal keeps the solution unique,
+//! which can happen before reaching the tier.
+//!
+//! Uniqueness is checked by searching for a solution different from the original,
+//! so the strategy used by the generator must return all possible values.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::progress::ProgressClock;
+use crate::{drive_search, Puzzle, Rater, Search, SearchHooks, SolveSettings, Stop, Strategy, Tier};
+
+/// Rejects solutions that are equal to a known solution.
+struct Other<'a, T: Puzzle> {
+    solution: &'a T,
+    positions: &'a [T::Pos],
+}
+
+impl<'a, T> SearchHooks<T> for Other<'a, T> where T: Puzzle {
+    fn accept(&mut self, state: &T) -> bool {
+        self.positions.iter().any(|&pos| state.get(pos) != self.solution.get(pos))
+    }
+}
+
+/// Returns `true` if a puzzle has no other solution than the given one.
+///
+/// The positions are compared to tell solutions apart.
+/// Returns `false` if the search stops after `SolveSettings::max_iterations`,
+/// since the puzzle could not be proven to have a unique solution.
+pub fn is_unique<T>(
+    puzzle: &T,
+    solution: &T,
+    positions: &[T::Pos],
+    strategy: Strategy<T>,
+    settings: &SolveSettings
+) -> bool
+    where T: Puzzle
+{
+    let mut search = Search::new(puzzle.clone());
+    let mut policy = strategy;
+    let mut hooks = Other {solution, positions};
+    let mut iterations: u64 = 0;
+    let mut clock = ProgressClock::new();
+    let stop = drive_search(settings, &mut iterations, &mut clock, &mut search, &mut policy, &mut hooks);
+    matches!(stop, Stop::Exhausted)
+}
+
+/// Returns a function that maps a grid position to the position rotated 180 degrees.
+///
+/// This is the symmetry commonly used for Sudoku.
+pub fn rotational(size: [usize; 2]) -> impl Fn([usize; 2]) -> [usize; 2] {
+    move |pos| [size[0] - 1 - pos[0], size[1] - 1 - pos[1]]
+}
+
+/// Maps a position to the position removed together with it.
+pub type Symmetry<P> = Box<dyn Fn(P) -> P>;
+
+/// Generates puzzles with a unique solution.
+pub struct Generator<T> where T: Puzzle {
+    /// Stores solve settings, used to check uniqueness.
+    pub settings: SolveSettings,
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// Maps a position to the position removed together with it, if any.
+    pub symmetry: Option<Symmetry<T::Pos>>,
+    /// Keeps removals only while the rating is at most the tier, if any.
+    pub target: Option<(Rater, Tier)>,
+}
+
+impl<T> Generator<T> where T: Puzzle {
+    /// Creates a new generator.
+    pub fn new(settings: SolveSettings) -> Generator<T> {
+        Generator {
+            settings,
+            seed: 0,
+            symmetry: None,
+            target: None,
+        }
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn set_seed(&mut self, val: u64) {
+        self.seed = val;
+    }
+
+    /// The seed of the random number generator.
+    pub fn seed(mut self, val: u64) -> Self {
+        self.set_seed(val);
+        self
+    }
+
+    /// Sets a function that maps a position to the position removed together with it.
+    pub fn set_symmetry<F>(&mut self, f: F) where F: Fn(T::Pos) -> T::Pos + 'static {
+        self.symmetry = Some(Box::new(f));
+    }
+
+    /// A function that maps a position to the position removed together with it,
+    /// e.g. `generate::rotational([9, 9])`.
+    pub fn symmetry<F>(mut self, f: F) -> Self where F: Fn(T::Pos) -> T::Pos + 'static {
+        self.set_symmetry(f);
+        self
+    }
+
+    /// Sets a rater and the highest tier of generated puzzles.
+    pub fn set_target(&mut self, rater: Rater, tier: Tier) {
+        self.target = Some((rater, tier));
+    }
+
+    /// A rater and the highest tier of generated puzzles.
+    ///
+    /// Removals that make the puzzle harder than the tier are undone.
+    /// The tier is a ceiling, not a guarantee: the result is easier than the tier
+    /// when no more values can be removed, so rate the result,
+    /// or try other seeds or solved puzzles if the tier is required.
+    pub fn target(mut self, rater: Rater, tier: Tier) -> Self {
+        self.set_target(rater, tier);
+        self
+    }
+
+    /// Generates a puzzle from a solved puzzle, by setting positions to the empty value.
+    ///
+    /// Each position, together with its symmetric position, is tried once in random order.
+    /// The strategy is used to check that the solution is unique.
+    pub fn generate(&self, solved: &T, positions: &[T::Pos], empty: T::Val, strategy: Strategy<T>) -> T {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut order: Vec<T::Pos> = positions.to_vec();
+        order.shuffle(&mut rng);
+
+        let mut puzzle = solved.clone();
+        for pos in order {
+            if puzzle.get(pos) == empty {continue};
+            let mut removed = vec![(pos, puzzle.get(pos))];
+            if let Some(ref symmetry) = self.symmetry {
+                let other = symmetry(pos);
+                if puzzle.get(other) != empty {
+                    removed.push((other, puzzle.get(other)));
+                }
+            }
+            for &(pos, _) in &removed {
+                puzzle.set(pos, empty);
+            }
+
+            let mut keep = is_unique(&puzzle, solved, positions, strategy, &self.settings);
+            if keep && let Some((ref rater, tier)) = self.target {
+                keep = rater.rate(&puzzle, &[strategy]).is_some_and(|rating| rating.tier <= tier);
+            }
+            if !keep {
+                for &(pos, val) in removed.iter().rev() {
+                    puzzle.set(pos, val);
+                }
+            }
+        }
+        puzzle
+    }
+}
@@ -0,0 +1,15 @@
+//! Puzzles shown on a 2D grid.
+
+use crate::Puzzle;
+
+/// Implemented by puzzles that can be shown as a 2D grid of characters.
+///
+/// This is used by the terminal viewer to draw the puzzle and highlight changes.
+pub trait Grid2D: Puzzle {
+    /// Returns the width and height of the grid.
+    fn grid_size(&self) -> [usize; 2];
+    /// Returns the character to show in a cell.
+    fn cell(&self, pos: [usize; 2]) -> char;
+    /// Returns the cell of a position, or `None` if the position is not shown.
+    fn grid_pos(&self, pos: Self::Pos) -> Option<[usize; 2]>;
+}
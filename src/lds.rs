@@ -0,0 +1,136 @@
+//! Limited discrepancy search.
+
+use crate::progress::ProgressClock;
+use crate::{drive_search, Puzzle, Search, SearchHooks, Solution, SolveSettings, Stop};
+
+/// Solves puzzles using limited discrepancy search.
+///
+/// A discrepancy is a choice that goes against the preferred value,
+/// which is the last value returned by the closure for picking options.
+/// The solver first searches with no discrepancies, then allows 1, 2 and so on.
+/// When the preferred values are good, this finds a solution faster than backtracking,
+/// since solutions that deviate from the heuristic a few times are tried first.
+///
+/// Each search with a higher budget repeats the paths allowed by lower budgets.
+/// The solver stops when a search is not limited by the budget, so it finds
+/// a solution if any exists, unless `max_discrepancies` is set.
+pub struct LimitedDiscrepancySolver<T> where T: Puzzle {
+    /// Stores the original state.
+    pub original: T,
+    /// Stores the state, with the changes and choices made.
+    pub search: Search<T>,
+    /// The maximum number of discrepancies, if any.
+    pub max_discrepancies: Option<u32>,
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+}
+
+impl<T> LimitedDiscrepancySolver<T> where T: Puzzle {
+    /// Creates a new solver.
+    pub fn new(puzzle: T, settings: SolveSettings) -> LimitedDiscrepancySolver<T> {
+        LimitedDiscrepancySolver {
+            original: puzzle.clone(),
+            search: Search::new(puzzle),
+            max_discrepancies: None,
+            settings,
+        }
+    }
+
+    /// Sets the maximum number of discrepancies, if any.
+    pub fn set_max_discrepancies(&mut self, val: Option<u32>) {
+        self.max_discrepancies = val;
+    }
+
+    /// The maximum number of discrepancies, if any.
+    pub fn max_discrepancies(mut self, val: Option<u32>) -> Self {
+        self.set_max_discrepancies(val);
+        self
+    }
+
+    /// Solves puzzle, using a closure to look for best position to set a value next,
+    /// and a closure for picking options in preferred order.
+    ///
+    /// The second closure returns possible values at a given position.
+    /// The last move in the list is the preferred one, and other moves count as discrepancies.
+    ///
+    /// The number of iterations in the solution includes all searches,
+    /// and `SolveSettings::max_iterations` limits the total.
+    pub fn solve<F, G>(self, f: F, g: G) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        self.solve_with_hooks(f, g, &mut ())
+    }
+
+    /// Solves puzzle like `solve`, but lets hooks observe and prune the search.
+    ///
+    /// The hooks are shared by all searches, so anything learned with a low budget
+    /// is used with higher budgets.
+    pub fn solve_with_hooks<F, G, H>(mut self, f: F, g: G, hooks: &mut H) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              H: SearchHooks<T>
+    {
+        let mut policy = (f, g);
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        let mut budget = 0;
+        loop {
+            if self.settings.debug {
+                self.settings.trace(format_args!("Discrepancies: {}", budget));
+            }
+            // Start from the original state.
+            self.search.reset();
+            self.search.discrepancy_budget = Some(budget);
+            match drive_search(&self.settings, &mut iterations, &mut clock, &mut self.search, &mut policy, hooks) {
+                Stop::Solved => {
+                    let mut solution = Solution::new(self.search.state,
+                        &self.original, &self.settings, iterations, None);
+                    hooks.stats(&mut solution.stats);
+                    return Some(solution);
+                }
+                Stop::DiscrepancyLimited if self.max_discrepancies.is_none_or(|max| budget < max) => budget += 1,
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+    use crate::NogoodStore;
+
+    fn solve(max: Option<u32>) -> Option<Vec<u8>> {
+        LimitedDiscrepancySolver::new(Row::new(4, 3, 8), SolveSettings::new())
+            .max_discrepancies(max)
+            .solve(Row::first_empty, Row::values)
+            .map(|solution| solution.puzzle.cells)
+    }
+
+    #[test]
+    fn fewest_discrepancies() {
+        // The preferred value is 1, so two cells must be 3.
+        assert_eq!(solve(None), Some(vec![1, 1, 3, 3]));
+        assert_eq!(solve(Some(2)), Some(vec![1, 1, 3, 3]));
+        assert_eq!(solve(Some(1)), None);
+    }
+
+    #[test]
+    fn hooks_keep_solution() {
+        let mut store = NogoodStore::new(1000);
+        for _ in 0..2 {
+            let solver = LimitedDiscrepancySolver::new(Row::new(4, 3, 8), SolveSettings::new());
+            let solution = solver.solve_with_hooks(Row::first_empty, Row::values, &mut store);
+            assert_eq!(solution.unwrap().puzzle.cells, vec![1, 1, 3, 3]);
+        }
+        assert!(!store.is_empty());
+    }
+
+    #[test]
+    fn no_solution() {
+        let solver = LimitedDiscrepancySolver::new(Row::new(3, 2, 7), SolveSettings::new());
+        assert!(solver.solve(Row::first_empty, Row::values).is_none());
+    }
+}
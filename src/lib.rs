@@ -4,7 +4,58 @@
 
 extern crate fnv;
 
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
+use std::sync::Mutex;
+
+use progress::ProgressClock;
+
+pub use aggregate::{combine_eq_with, combine_strategies, combine_with, Aggregation, CombineSettings, TieBreak};
+pub use beam::BeamSolver;
+pub use best_first::BestFirstSolver;
+pub use domains::{BitDomains, Domains};
+pub use estimate::TreeEstimate;
+pub use grid::Grid2D;
+pub use lds::LimitedDiscrepancySolver;
+pub use local::LocalSearchSolver;
+pub use mcts::MonteCarloSolver;
+pub use nogood::NogoodStore;
+pub use progress::{Progress, ProgressFn, ProgressInterval};
+pub use rating::{Measurements, Rater, Rating, Tier};
+pub use search::{Policy, Search, Step};
+pub use select::Variables;
+pub use trail::Trail;
+pub use trace::{Trace, TraceEvent};
+pub use transposition::TranspositionTable;
+pub use tree::{NodeMark, TreeNode, TreeRecorder};
+#[cfg(feature = "viewer")]
+pub use viewer::Viewer;
+
+mod aggregate;
+mod beam;
+mod best_first;
+mod domains;
+mod estimate;
+mod grid;
+mod lds;
+mod local;
+mod mcts;
+mod nogood;
+mod progress;
+mod rating;
+mod search;
+mod trail;
+mod trace;
+mod transposition;
+mod tree;
+#[cfg(feature = "viewer")]
+mod viewer;
+#[cfg(test)]
+mod testing;
+
+pub mod generate;
+pub mod order;
+pub mod propagate;
+pub mod select;
 
 /// Implemented by puzzles.
 ///
@@ -35,8 +86,19 @@ pub trait Puzzle: Clone {
     fn is_solved(&self) -> bool;
     /// Removes values from other puzzle to show changes.
     fn remove(&mut self, other: &Self);
+    /// Writes puzzle out to a text output.
+    ///
+    /// This is used instead of `print` when debug output is sent to `SolveSettings::sink`.
+    /// The default implementation writes nothing.
+    fn write_to(&self, _w: &mut dyn fmt::Write) -> fmt::Result {Ok(())}
 }
 
+/// Receives debug output from solvers, one message at a time.
+///
+/// The function can change its own state, e.g. to append messages to a log.
+/// `SolveSettings` keeps it in a mutex, so the settings can still be shared between threads.
+pub type Sink = Box<dyn FnMut(&str) + Send>;
+
 /// Stores settings for solver.
 ///
 /// Default settings:
@@ -58,6 +120,14 @@ pub struct SolveSettings {
     pub max_iterations: Option<u64>,
     /// Whether to print every million iteration.
     pub print_millions: bool,
+    /// The maximum number of choices on the stack, if any.
+    pub max_depth: Option<usize>,
+    /// Receives debug output instead of the terminal, if any.
+    pub sink: Option<Mutex<Sink>>,
+    /// Receives progress reports, if any.
+    pub on_progress: Option<ProgressFn>,
+    /// How often progress is reported.
+    pub progress_interval: ProgressInterval,
 }
 
 impl SolveSettings {
@@ -70,6 +140,10 @@ impl SolveSettings {
             sleep_ms: None,
             max_iterations: None,
             print_millions: false,
+            max_depth: None,
+            sink: None,
+            on_progress: None,
+            progress_interval: ProgressInterval::Iterations(1_000_000),
         }
     }
 
@@ -155,11 +229,141 @@ impl SolveSettings {
         self.print_millions = val;
     }
 
-    /// Prints every million iteration to standard error output.
+    /// Prints every million iteration to standard error output, or the sink if set.
+    ///
+    /// Nothing is printed when `on_progress` is set, since it receives the reports instead.
     pub fn print_millions(mut self, val: bool) -> Self {
         self.set_print_millions(val);
         self
     }
+
+    /// Sets a function that receives progress reports.
+    pub fn set_on_progress<F>(&mut self, f: F) where F: Fn(&Progress) + Send + Sync + 'static {
+        self.on_progress = Some(Box::new(f));
+    }
+
+    /// A function that receives progress reports.
+    ///
+    /// The backtracking solvers estimate the fraction of the search tree explored
+    /// from the number of values tried at each choice (Knuth's estimator),
+    /// which is used to estimate the remaining time.
+    pub fn on_progress<F>(mut self, f: F) -> Self where F: Fn(&Progress) + Send + Sync + 'static {
+        self.set_on_progress(f);
+        self
+    }
+
+    /// Sets how often progress is reported.
+    pub fn set_progress_interval(&mut self, val: ProgressInterval) {
+        self.progress_interval = val;
+    }
+
+    /// How often progress is reported, by default every million iterations.
+    pub fn progress_interval(mut self, val: ProgressInterval) -> Self {
+        self.set_progress_interval(val);
+        self
+    }
+
+    /// Sets the maximum depth of choices before backtracking, if any.
+    pub fn set_maybe_max_depth(&mut self, val: Option<usize>) {
+        self.max_depth = val;
+    }
+
+    /// The maximum depth of choices before backtracking, if any.
+    pub fn maybe_max_depth(mut self, val: Option<usize>) -> Self {
+        self.set_maybe_max_depth(val);
+        self
+    }
+
+    /// Sets the maximum depth of choices before backtracking.
+    ///
+    /// States at this depth are checked for being solved,
+    /// but no further guesses are made.
+    pub fn set_max_depth(&mut self, val: usize) {
+        self.max_depth = Some(val);
+    }
+
+    /// The maximum depth of choices before backtracking.
+    pub fn max_depth(mut self, val: usize) -> Self {
+        self.set_max_depth(val);
+        self
+    }
+
+    /// Sets a function that receives debug output instead of the terminal.
+    ///
+    /// Puzzle states are written with `Puzzle::write_to`.
+    pub fn set_sink<F>(&mut self, f: F) where F: FnMut(&str) + Send + 'static {
+        self.sink = Some(Mutex::new(Box::new(f)));
+    }
+
+    /// A function that receives debug output instead of the terminal.
+    pub fn sink<F>(mut self, f: F) -> Self where F: FnMut(&str) + Send + 'static {
+        self.set_sink(f);
+        self
+    }
+
+    /// Writes a debug message to the sink, or standard error output if there is no sink.
+    pub fn trace(&self, msg: fmt::Arguments) {
+        if !self.write_sink(&msg.to_string()) {
+            eprintln!("{}", msg);
+        }
+    }
+
+    /// Writes a debug message to the sink, or standard output if there is no sink.
+    pub(crate) fn trace_stdout(&self, msg: fmt::Arguments) {
+        if !self.write_sink(&msg.to_string()) {
+            println!("{}", msg);
+        }
+    }
+
+    /// Writes text to the sink, returning `false` if there is no sink.
+    fn write_sink(&self, text: &str) -> bool {
+        match self.sink {
+            Some(ref sink) => {
+                // A sink that panicked is still used for later messages.
+                let mut sink = sink.lock().unwrap_or_else(|err| err.into_inner());
+                (*sink)(text);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Writes a puzzle state to the sink using `Puzzle::write_to`,
+    /// or prints it with `Puzzle::print` if there is no sink.
+    pub fn trace_state<T: Puzzle>(&self, state: &T) {
+        if self.sink.is_some() {
+            let mut text = String::new();
+            if state.write_to(&mut text).is_ok() && !text.is_empty() {
+                self.write_sink(&text);
+            }
+        } else {
+            state.print();
+        }
+    }
+
+    /// Reports progress to `on_progress`, or prints every million iteration if enabled.
+    ///
+    /// Every million iteration is only printed when not debugging,
+    /// since the debug output shows the iterations already.
+    /// The closure returns the depth and the fraction of the search tree explored, if known.
+    pub(crate) fn report_progress<F>(&self, clock: &mut ProgressClock, iterations: u64, progress: F)
+        where F: FnOnce() -> (usize, Option<f64>)
+    {
+        match self.on_progress {
+            Some(ref f) => {
+                if let Some(progress) = clock.report(self.progress_interval, iterations, progress) {
+                    f(&progress);
+                }
+            }
+            None if self.print_millions && !self.debug => {
+                let interval = ProgressInterval::Iterations(1_000_000);
+                if let Some(progress) = clock.report(interval, iterations, progress) {
+                    self.trace(format_args!("Iteration: {}mill", progress.iterations / 1_000_000));
+                }
+            }
+            None => {}
+        }
+    }
 }
 
 /// Contains solution.
@@ -170,6 +374,264 @@ pub struct Solution<T> {
     pub iterations: u64,
     /// The strategy that found the solution.
     pub strategy: Option<usize>,
+    /// The statistics reported by the search hooks.
+    pub stats: SearchStats,
+}
+
+/// Statistics reported by search hooks, see `SearchHooks::stats`.
+///
+/// Hooks that are reused across solves report their totals so far,
+/// including earlier solves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of states found in a transposition table.
+    pub hits: u64,
+    /// The number of states not found in a transposition table.
+    pub misses: u64,
+}
+
+/// Tells why a search stopped.
+pub enum SolveOutcome<T> {
+    /// A solution was found.
+    Solved(Solution<T>),
+    /// All choices were tried without finding a solution.
+    ///
+    /// This proves that the puzzle has no solution.
+    Exhausted,
+    /// No solution was found, but `SolveSettings::max_depth` cut the search short,
+    /// or `Search::discrepancy_budget` if it is set.
+    ///
+    /// There might be a solution deeper in the search tree.
+    DepthLimited,
+    /// The search stopped after `SolveSettings::max_iterations`.
+    IterationLimited,
+}
+
+impl<T> SolveOutcome<T> {
+    /// Returns the solution, if any.
+    pub fn solution(self) -> Option<Solution<T>> {
+        match self {
+            SolveOutcome::Solved(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if a solution was found.
+    pub fn is_solved(&self) -> bool {
+        matches!(self, SolveOutcome::Solved(_))
+    }
+
+    /// Returns `true` if the search was not cut short by a limit,
+    /// such that the outcome is either a solution or a proof of no solution.
+    pub fn is_complete(&self) -> bool {
+        matches!(self, SolveOutcome::Solved(_) | SolveOutcome::Exhausted)
+    }
+}
+
+impl<T> Solution<T> where T: Puzzle {
+    /// Creates a solution, removing the original values if `SolveSettings::difference` is set.
+    fn new(
+        mut puzzle: T,
+        original: &T,
+        settings: &SolveSettings,
+        iterations: u64,
+        strategy: Option<usize>
+    ) -> Solution<T> {
+        if settings.difference {
+            puzzle.remove(original);
+        }
+        Solution {puzzle, iterations, strategy, stats: SearchStats::default()}
+    }
+}
+
+/// The reason a single search stopped.
+enum Stop {
+    Solved,
+    Exhausted,
+    DepthLimited,
+    DiscrepancyLimited,
+    IterationLimited,
+}
+
+/// Calls a step function until the search stops, counting iterations.
+///
+/// This sleeps before each step in debug mode, reports progress after each step,
+/// and stops after `SolveSettings::max_iterations`.
+/// The iteration count is passed to the step function,
+/// and the progress closure returns the depth and the fraction explored of the searches.
+fn drive<C, F, R>(
+    settings: &SolveSettings,
+    iterations: &mut u64,
+    clock: &mut ProgressClock,
+    searches: &mut C,
+    mut step: F,
+    progress: R
+) -> Stop
+    where C: ?Sized,
+          F: FnMut(&mut C, u64) -> Step,
+          R: Fn(&C) -> (usize, Option<f64>)
+{
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    loop {
+        if settings.debug && let Some(ms) = settings.sleep_ms {
+            sleep(Duration::from_millis(ms));
+        }
+        *iterations += 1;
+        if settings.max_iterations.is_some_and(|max| *iterations > max) {
+            return Stop::IterationLimited;
+        }
+        match step(searches, *iterations) {
+            Step::Continue => settings.report_progress(clock, *iterations, || progress(searches)),
+            Step::Solved => return Stop::Solved,
+            Step::Exhausted => return Stop::Exhausted,
+            Step::DepthLimited => return Stop::DepthLimited,
+            Step::DiscrepancyLimited => return Stop::DiscrepancyLimited,
+        }
+    }
+}
+
+/// Drives a single search with a policy and hooks.
+fn drive_search<T, P, H>(
+    settings: &SolveSettings,
+    iterations: &mut u64,
+    clock: &mut ProgressClock,
+    search: &mut Search<T>,
+    policy: &mut P,
+    hooks: &mut H
+) -> Stop
+    where T: Puzzle, P: Policy<T>, H: SearchHooks<T>
+{
+    drive(settings, iterations, clock, search,
+        |search, iterations| search.step(policy, hooks, settings, iterations),
+        |search| (search.choice.len(), Some(search.explored())))
+}
+
+/// Implemented by hooks that observe or prune the search.
+///
+/// Every method has a default implementation that does nothing,
+/// so `()` is used when no hooks are needed.
+/// Two hooks can be used at the same time by putting them in a tuple.
+pub trait SearchHooks<T: Puzzle> {
+    /// Returns `true` if a value is known to fail at a position.
+    fn prune(&mut self, _state: &T, _pos: T::Pos, _val: T::Val) -> bool {false}
+    /// Returns `true` if a state is known to have no solution.
+    fn is_dead(&mut self, _state: &T) -> bool {false}
+    /// Called when a state is proven to have no solution.
+    ///
+    /// The positions of `choice` are the guesses that lead to the state.
+    fn dead_end(&mut self, _state: &T, _choice: &[(T::Pos, Vec<T::Val>)]) {}
+    /// Called after a value is set by a guess.
+    ///
+    /// The depth is the number of guesses that lead to the state, including this one.
+    fn guess(&mut self, _state: &T, _pos: T::Pos, _val: T::Val, _depth: usize) {}
+    /// Called when a state is solved.
+    ///
+    /// The depth is the number of guesses that lead to the state.
+    fn solved(&mut self, _state: &T, _depth: usize) {}
+    /// Called after a value is set by a simple step.
+    fn simple(&mut self, _state: &T, _pos: T::Pos, _val: T::Val) {}
+    /// Called after the last guess is undone, together with the simple steps that followed it.
+    ///
+    /// The depth is the number of guesses that remain.
+    fn backtrack(&mut self, _state: &T, _depth: usize) {}
+    /// Returns `false` to reject a solved state, such that the search continues.
+    ///
+    /// The search backtracks from a rejected state, but `dead_end` is not called
+    /// for it or the states leading to it, since they have a solution.
+    /// This is used to look for other solutions than those found already.
+    fn accept(&mut self, _state: &T) -> bool {true}
+    /// Adds statistics of the hooks, which are reported with the solution.
+    fn stats(&self, _stats: &mut SearchStats) {}
+}
+
+impl<T: Puzzle> SearchHooks<T> for () {}
+
+impl<T, H> SearchHooks<T> for &mut H
+    where T: Puzzle, H: SearchHooks<T>
+{
+    fn prune(&mut self, state: &T, pos: T::Pos, val: T::Val) -> bool {
+        (**self).prune(state, pos, val)
+    }
+
+    fn is_dead(&mut self, state: &T) -> bool {
+        (**self).is_dead(state)
+    }
+
+    fn dead_end(&mut self, state: &T, choice: &[(T::Pos, Vec<T::Val>)]) {
+        (**self).dead_end(state, choice)
+    }
+
+    fn guess(&mut self, state: &T, pos: T::Pos, val: T::Val, depth: usize) {
+        (**self).guess(state, pos, val, depth)
+    }
+
+    fn solved(&mut self, state: &T, depth: usize) {
+        (**self).solved(state, depth)
+    }
+
+    fn simple(&mut self, state: &T, pos: T::Pos, val: T::Val) {
+        (**self).simple(state, pos, val)
+    }
+
+    fn backtrack(&mut self, state: &T, depth: usize) {
+        (**self).backtrack(state, depth)
+    }
+
+    fn accept(&mut self, state: &T) -> bool {
+        (**self).accept(state)
+    }
+
+    fn stats(&self, stats: &mut SearchStats) {
+        (**self).stats(stats)
+    }
+}
+
+impl<T, A, B> SearchHooks<T> for (A, B)
+    where T: Puzzle, A: SearchHooks<T>, B: SearchHooks<T>
+{
+    fn prune(&mut self, state: &T, pos: T::Pos, val: T::Val) -> bool {
+        self.0.prune(state, pos, val) || self.1.prune(state, pos, val)
+    }
+
+    fn is_dead(&mut self, state: &T) -> bool {
+        self.0.is_dead(state) || self.1.is_dead(state)
+    }
+
+    fn dead_end(&mut self, state: &T, choice: &[(T::Pos, Vec<T::Val>)]) {
+        self.0.dead_end(state, choice);
+        self.1.dead_end(state, choice);
+    }
+
+    fn guess(&mut self, state: &T, pos: T::Pos, val: T::Val, depth: usize) {
+        self.0.guess(state, pos, val, depth);
+        self.1.guess(state, pos, val, depth);
+    }
+
+    fn solved(&mut self, state: &T, depth: usize) {
+        self.0.solved(state, depth);
+        self.1.solved(state, depth);
+    }
+
+    fn simple(&mut self, state: &T, pos: T::Pos, val: T::Val) {
+        self.0.simple(state, pos, val);
+        self.1.simple(state, pos, val);
+    }
+
+    fn backtrack(&mut self, state: &T, depth: usize) {
+        self.0.backtrack(state, depth);
+        self.1.backtrack(state, depth);
+    }
+
+    fn accept(&mut self, state: &T) -> bool {
+        self.0.accept(state) && self.1.accept(state)
+    }
+
+    fn stats(&self, stats: &mut SearchStats) {
+        self.0.stats(stats);
+        self.1.stats(stats);
+    }
 }
 
 /// Solves puzzles using back tracking.
@@ -178,13 +640,11 @@ pub struct BackTrackSolver<T>
 {
     /// Stores the original state.
     pub original: T,
-    /// Stores the state.
-    pub state: T,
-    /// Stores the previous values of a position before making a choice.
-    /// If the flag is true, the value was inserted due to a simple choice.
-    pub prevs: Vec<(T::Pos, T::Val, bool)>,
-    /// Stores the choices for the states.
-    pub choice: Vec<(T::Pos, Vec<T::Val>)>,
+    /// Stores the state, with the changes and choices made.
+    ///
+    /// Every solve starts over from the original state.
+    /// For a custom search driver, use `Search` with `Search::checkpoint` and `Search::restore`.
+    pub search: Search<T>,
     /// Stores solve settings.
     pub settings: SolveSettings,
 }
@@ -196,132 +656,162 @@ impl<T> BackTrackSolver<T>
     pub fn new(puzzle: T, settings: SolveSettings) -> BackTrackSolver<T> {
         BackTrackSolver {
             original: puzzle.clone(),
-            state: puzzle,
-            prevs: vec![],
-            choice: vec![],
+            search: Search::new(puzzle),
             settings: settings,
         }
     }
 
+    /// Returns the current state, which used to be the field `state` before version 0.8.
+    pub fn state(&self) -> &T {
+        &self.search.state
+    }
+
     /// Solves puzzle, using a closure to look for best position to set a value next,
     /// and a closure for picking options in preferred order.
     ///
     /// The second closure returns possible values at a given position.
     /// The last move in the list has highest priority, because the solver pops the values in turn.
-    pub fn solve<F, G>(mut self, mut f: F, mut g: G) -> Option<Solution<T>>
+    pub fn solve<F, G>(self, f: F, g: G) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        self.solve_with_hooks(f, g, &mut ())
+    }
+
+    /// Finds a random solution, trying values in random order.
+    ///
+    /// The seed decides the random number generator, so the same seed gives the same solution.
+    /// This is useful to get varied complete solutions, e.g. to start generating puzzles from,
+    /// but the solutions are not sampled uniformly.
+    pub fn random_solution<F, G>(self, seed: u64, f: F, g: G) -> Option<Solution<T>>
         where F: FnMut(&T) -> Option<T::Pos>,
               G: FnMut(&T, T::Pos) -> Vec<T::Val>
     {
-        use std::thread::sleep;
-        use std::time::Duration;
+        self.solve(f, order::random(seed, g))
+    }
+
+    /// Finds a random solution like `random_solution`,
+    /// picking the position with lowest key and breaking ties at random.
+    pub fn random_solution_ties<K, G>(self, seed: u64, key: K, g: G) -> Option<Solution<T>>
+        where T: Variables,
+              K: FnMut(&T, T::Pos) -> usize,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        // Tie breaking uses a different seed than value order, so the random streams are independent.
+        self.solve(select::random_ties(seed ^ 0x9E37_79B9_7F4A_7C15, key), order::random(seed, g))
+    }
+
+    /// Estimates the number of nodes in the search tree, without solving the puzzle.
+    ///
+    /// Uses random probes with the same closures as `solve` (Knuth's estimator).
+    /// This is useful to compare strategies before committing to a long search.
+    /// Simple steps and the maximum depth are taken from the solve settings.
+    ///
+    /// The seed picks the random probes, so the same seed gives the same estimate.
+    pub fn estimate_tree_size<F, G>(&self, samples: usize, seed: u64, f: F, g: G) -> TreeEstimate
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        estimate::estimate_tree_size(&self.original, &self.settings, samples, seed, f, g)
+    }
+
+    /// Solves puzzle like `solve`, but lets hooks prune the search.
+    ///
+    /// The hooks are borrowed, so anything they learn is kept after solving
+    /// and can be reused in later attempts on the same puzzle.
+    pub fn solve_with_hooks<F, G, H>(self, f: F, g: G, hooks: &mut H) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              H: SearchHooks<T>
+    {
+        self.solve_outcome(f, g, hooks).solution()
+    }
 
+    /// Solves puzzle like `solve_with_hooks`, but tells why the search stopped.
+    ///
+    /// Use this with `SolveSettings::max_depth` or `SolveSettings::max_iterations`
+    /// to tell a proof of no solution apart from a search that was cut short.
+    /// Pass `&mut ()` when no hooks are needed.
+    pub fn solve_outcome<F, G, H>(mut self, f: F, g: G, hooks: &mut H) -> SolveOutcome<T>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              H: SearchHooks<T>
+    {
         let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        let stop = self.run(&mut (f, g), hooks, &mut iterations, &mut clock);
+        self.outcome(stop, iterations, hooks)
+    }
+
+    /// Solves puzzle using iterative deepening.
+    ///
+    /// The search is repeated with maximum depth 0, 1, 2 and so on,
+    /// until a solution is found or the search is no longer cut short by the depth.
+    /// This finds a solution with fewest choices, and works for puzzles with
+    /// very deep or unbounded branches, where depth first search might never return.
+    ///
+    /// If `SolveSettings::max_depth` is set, the depth is not increased beyond it.
+    /// The number of iterations includes all searches,
+    /// and `SolveSettings::max_iterations` limits the total.
+    /// Pass `&mut ()` when no hooks are needed.
+    pub fn iterative_deepening<F, G, H>(mut self, f: F, g: G, hooks: &mut H) -> SolveOutcome<T>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+              H: SearchHooks<T>
+    {
+        let limit = self.settings.max_depth;
+        let mut policy = (f, g);
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        let mut depth = 0;
         loop {
             if self.settings.debug {
-                if let Some(ms) = self.settings.sleep_ms {
-                    sleep(Duration::from_millis(ms));
-                }
-            }
-            if self.settings.solve_simple {
-                let ref mut prevs = self.prevs;
-                self.state.solve_simple(|state, pos, val| {
-                    prevs.push((pos, state.get(pos), true));
-                    state.set(pos, val);
-                });
-            }
-            if self.settings.debug {
-                self.state.print();
+                self.settings.trace(format_args!("Max depth: {}", depth));
             }
-            iterations += 1;
-            if let Some(max_iterations) = self.settings.max_iterations {
-                if iterations > max_iterations {
-                    return None;
-                }
-            }
-            if self.state.is_solved() {
-                if self.settings.debug {
-                    eprintln!("Solved! Iterations: {}", iterations);
-                }
-                if self.settings.difference {
-                    self.state.remove(&self.original);
-                }
-                return Some(Solution { puzzle: self.state, iterations: iterations, strategy: None });
+            self.settings.max_depth = Some(depth);
+            match self.run(&mut policy, hooks, &mut iterations, &mut clock) {
+                Stop::DepthLimited if limit.is_none_or(|max| depth < max) => depth += 1,
+                stop => return self.outcome(stop, iterations, hooks),
             }
+        }
+    }
 
-            let empty = f(&self.state);
-            let mut possible = match empty {
-                None => vec![],
-                Some(x) => g(&self.state, x)
-            };
-            if possible.len() == 0 {
-                loop {
-                    if self.choice.len() == 0 {
-                        if self.settings.debug {
-                            // No more possible choices.
-                            eprintln!("No more possible choices");
-                        }
-                        return None;
-                    }
-                    let (pos, mut possible) = self.choice.pop().unwrap();
-                    if let Some(new_val) = possible.pop() {
-                        // Try next choice.
-                        while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
-                            self.state.set(old_pos, old_val);
-                            if !simple {break}
-                        }
-                        self.prevs.push((pos, self.state.get(pos), false));
-                        self.state.set(pos, new_val);
-                        self.choice.push((pos, possible));
-                        if self.settings.debug {
-                            eprintln!("Try   {:?}, {:?} depth ch: {} prev: {} (failed at {:?}) it: {}",
-                                pos, new_val, self.choice.len(), self.prevs.len(), empty, iterations);
-                        } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                            eprintln!("Iteration: {}mill", iterations / 1_000_000);
-                        }
-                        break;
-                    } else {
-                        let mut undo = false;
-                        while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
-                            self.state.set(old_pos, old_val);
-                            undo = true;
-                            if !simple {break}
-                        }
-                        if !undo {
-                            // No more possible choices.
-                            return None;
-                        }
-                    }
-                }
-            } else {
-                let empty = empty.unwrap();
-                // Put in the first guess.
-                let v = possible.pop().unwrap();
-                self.prevs.push((empty, self.state.get(empty), false));
-                self.state.set(empty, v);
-                self.choice.push((empty, possible));
-                if self.settings.debug {
-                    eprintln!("Guess {:?}, {:?} depth ch: {} prev: {} it: {}",
-                        empty, v, self.choice.len(), self.prevs.len(), iterations);
-                } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                    eprintln!("Iteration: {}mill", iterations / 1_000_000);
-                }
+    fn outcome<H>(self, stop: Stop, iterations: u64, hooks: &H) -> SolveOutcome<T>
+        where H: SearchHooks<T>
+    {
+        match stop {
+            Stop::Solved => {
+                let mut solution = Solution::new(self.search.state, &self.original,
+                    &self.settings, iterations, None);
+                hooks.stats(&mut solution.stats);
+                SolveOutcome::Solved(solution)
             }
+            Stop::Exhausted => SolveOutcome::Exhausted,
+            Stop::DepthLimited | Stop::DiscrepancyLimited => SolveOutcome::DepthLimited,
+            Stop::IterationLimited => SolveOutcome::IterationLimited,
         }
     }
+
+    fn run<P, H>(&mut self, policy: &mut P, hooks: &mut H, iterations: &mut u64, clock: &mut ProgressClock) -> Stop
+        where P: Policy<T>, H: SearchHooks<T>
+    {
+        // Start from the original state.
+        self.search.reset();
+        drive_search(&self.settings, iterations, clock, &mut self.search, policy, hooks)
+    }
 }
 
+/// A strategy, which is a function picking the position to set a value next,
+/// and a function returning the possible values in preferred order.
+pub type Strategy<T> = (fn(&T) -> Option<<T as Puzzle>::Pos>, fn(&T, <T as Puzzle>::Pos) -> Vec<<T as Puzzle>::Val>);
+
 /// Solves puzzle using multiple strategies at the same time.
 /// Each strategy is evaluated one step by turn until a solution is found.
 pub struct MultiBackTrackSolver<T>
     where T: Puzzle
 {
-    /// Stores the states.
-    pub states: Vec<T>,
-    /// Stores previous values before making choices.
-    /// The flags is true when made a simple choice.
-    pub prevs: Vec<Vec<(T::Pos, T::Val, bool)>>,
-    /// Stores the choices for the states.
-    pub choice: Vec<Vec<(T::Pos, Vec<T::Val>)>>,
+    /// Stores the states, with the changes and choices made, for each strategy.
+    pub searches: Vec<Search<T>>,
     /// Stores solve settings.
     pub settings: SolveSettings,
 }
@@ -332,13 +822,17 @@ impl<T> MultiBackTrackSolver<T>
     /// Creates a new solver.
     pub fn new(settings: SolveSettings) -> MultiBackTrackSolver<T> {
         MultiBackTrackSolver {
-            states: vec![],
-            prevs: vec![],
-            choice: vec![],
+            searches: vec![],
             settings: settings,
         }
     }
 
+    /// Returns the current state of each strategy,
+    /// which used to be the field `states` before version 0.8.
+    pub fn states(&self) -> Vec<&T> {
+        self.searches.iter().map(|search| &search.state).collect()
+    }
+
     /// Solves puzzle, using a closure to look for best position to set a value next,
     /// and a closure for picking options in preferred order.
     ///
@@ -353,114 +847,40 @@ impl<T> MultiBackTrackSolver<T>
         puzzle: T,
         strategies: &[(fn(&T) -> Option<T::Pos>, fn(&T, T::Pos) -> Vec<T::Val>)]
     ) -> Option<Solution<T>> {
-        use std::thread::sleep;
-        use std::time::Duration;
-
-        let origin = puzzle.clone();
-        self.states = vec![puzzle; strategies.len()];
-        self.prevs = vec![vec![]; strategies.len()];
-        self.choice = vec![vec![]; strategies.len()];
+        self.searches = (0..strategies.len()).map(|_| {
+            let mut search = Search::new(puzzle.clone());
+            search.stdout = true;
+            search
+        }).collect();
+        let settings = &self.settings;
         let mut iterations: u64 = 0;
-        loop {
-            if self.settings.debug {
-                if let Some(ms) = self.settings.sleep_ms {
-                    sleep(Duration::from_millis(ms));
-                }
-            }
-
-            iterations += 1;
-            if let Some(max_iterations) = self.settings.max_iterations {
-                if iterations > max_iterations {
-                    return None;
-                }
-            }
-
-            for i in 0..strategies.len() {
-                let ref mut state = self.states[i];
-                let ref mut prevs = self.prevs[i];
-                let ref mut choice = self.choice[i];
-                let (f, g) = strategies[i];
-
-                if self.settings.solve_simple {
-                    state.solve_simple(|state, pos, val| {
-                        prevs.push((pos, state.get(pos), true));
-                        state.set(pos, val)
-                    });
-                }
-                if self.settings.debug {
-                    println!("Strategy {}", i);
-                    state.print();
+        let mut clock = ProgressClock::new();
+        let mut last = 0;
+        let stop = drive(settings, &mut iterations, &mut clock, &mut self.searches[..], |searches, iterations| {
+            for (i, search) in searches.iter_mut().enumerate() {
+                let mut policy = strategies[i];
+                if settings.debug {
+                    settings.trace_stdout(format_args!("Strategy {}", i));
                 }
-                if state.is_solved() {
-                    if self.settings.debug {
-                        println!("Solved! Iterations: {}", iterations);
-                    }
-                    if self.settings.difference {
-                        state.remove(&origin);
-                    }
-                    return Some(Solution { puzzle: state.clone(), iterations: iterations, strategy: Some(i) });
-                }
-
-                let empty = f(&state);
-                let mut possible = match empty {
-                    None => vec![],
-                    Some(x) => g(&state, x)
-                };
-                if possible.len() == 0 {
-                    // println!("No possible at {:?}", empty);
-                    loop {
-                        if choice.len() == 0 {
-                            if self.settings.debug {
-                                // No more possible choices.
-                                println!("No more possible choices");
-                            }
-                            return None;
-                        }
-                        let (pos, mut possible) = choice.pop().unwrap();
-                        if let Some(new_val) = possible.pop() {
-                            // Try next choice.
-                            while let Some((old_pos, old_val, simple)) = prevs.pop() {
-                                state.set(old_pos, old_val);
-                                if !simple {break}
-                            }
-                            prevs.push((pos, state.get(pos), false));
-                            state.set(pos, new_val);
-                            choice.push((pos, possible));
-                            if self.settings.debug {
-                                eprintln!("Try   {:?}, {:?} depth ch: {} prev: {} (failed at {:?}) it: {}",
-                                    pos, new_val, self.choice.len(), self.prevs.len(), empty, iterations);
-                            } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                                eprintln!("Iteration: {}mill", iterations / 1_000_000);
-                            }
-                            break;
-                        } else {
-                            let mut undo = false;
-                            while let Some((old_pos, old_val, simple)) = prevs.pop() {
-                                state.set(old_pos, old_val);
-                                undo = true;
-                                if !simple {break}
-                            }
-                            if !undo {
-                                // No more possible choices.
-                                return None;
-                            }
-                        }
-                    }
-                } else {
-                    let empty = empty.unwrap();
-                    // Put in the first guess.
-                    let v = possible.pop().unwrap();
-                    prevs.push((empty, state.get(empty), false));
-                    state.set(empty, v);
-                    choice.push((empty, possible));
-                    if self.settings.debug {
-                        eprintln!("Guess {:?}, {:?} depth ch: {} prev: {} it: {}",
-                            empty, v, self.choice.len(), self.prevs.len(), iterations);
-                    } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                        eprintln!("Iteration: {}mill", iterations / 1_000_000);
+                match search.step(&mut policy, &mut (), settings, iterations) {
+                    Step::Continue => {}
+                    step => {
+                        last = i;
+                        return step;
                     }
                 }
             }
+            Step::Continue
+        }, |searches| {
+            // Report the strategy that explored the most.
+            searches.iter().map(|search| (search.choice.len(), Some(search.explored())))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .unwrap_or((0, None))
+        });
+        match stop {
+            Stop::Solved => Some(Solution::new(self.searches.swap_remove(last).state,
+                &puzzle, &self.settings, iterations, Some(last))),
+            _ => None,
         }
     }
 }
@@ -469,29 +889,26 @@ impl<T> MultiBackTrackSolver<T>
 ///
 /// This is used to combine strategies into a new one.
 /// Sometimes this is better than using either strategy.
+///
+/// Items with equal total priority are kept in the order they are seen first,
+/// so the result is the same for every run.
+///
+/// For weights and other aggregation methods, see `combine_with`.
 pub fn combine<T>(lists: Vec<Vec<T>>) -> Vec<T>
 	where T: Clone + ::std::hash::Hash + Eq
 {
-	let mut priority: fnv::FnvHashMap<T, usize> = fnv::FnvHashMap::default();
-	for list in &lists {
-		for (i, ch) in list.iter().enumerate() {
-			if priority.contains_key(ch) {
-				let old = priority[ch];
-				priority.insert(ch.clone(), old + i);
-			} else {
-				priority.insert(ch.clone(), i);
-			}
-		}
-	}
-
-	let keys: Vec<&T> = priority.keys().collect();
-	let mut inds: Vec<usize> = (0..keys.len()).collect();
-	inds.sort_by_key(|&ind| priority[keys[ind]]);
-	let mut res = Vec::with_capacity(keys.len());
-	for &ind in &inds {
-		res.push(keys[ind].clone());
-	}
-	res
+	combine_with(lists, &CombineSettings::new())
+}
+
+/// Combines multiple priority lists together, comparing items with `PartialEq`.
+///
+/// This works like `combine`, but does not require `Hash`,
+/// e.g. for `f64` values or structs that can not be hashed.
+/// Items are compared one by one, so it is slower for long lists.
+pub fn combine_eq<T>(lists: Vec<Vec<T>>) -> Vec<T>
+	where T: PartialEq
+{
+	combine_eq_with(lists, &CombineSettings::new())
 }
 
 /// Stores settings for entropy solver.
@@ -566,13 +983,8 @@ impl EntropySolveSettings {
 pub struct EntropyBackTrackSolver<T> where T: Puzzle {
     /// Stores the original state.
     pub original: T,
-    /// Stores the state.
-    pub state: T,
-    /// Stores the previous values of a position before making a choice.
-    /// If the flag is true, the value was inserted due to a simple choice.
-    pub prevs: Vec<(T::Pos, T::Val, bool)>,
-    /// Stores the choices for the states.
-    pub choice: Vec<(T::Pos, Vec<T::Val>)>,
+    /// Stores the state, with the changes and choices made.
+    pub search: Search<T>,
     /// The initial choices.
     pub start_choice: Vec<(T::Pos, Vec<T::Val>)>,
     /// Stores weights of choices.
@@ -594,9 +1006,7 @@ impl<T> EntropyBackTrackSolver<T> where T: Puzzle {
         let weights = start_choice.iter().map(|n| vec![1.0; n.1.len()]).collect();
         EntropyBackTrackSolver {
             original: puzzle.clone(),
-            prevs: vec![],
-            state: puzzle,
-            choice: vec![],
+            search: Search::new(puzzle),
             start_choice,
             weights,
             entropy_settings,
@@ -604,45 +1014,28 @@ impl<T> EntropyBackTrackSolver<T> where T: Puzzle {
         }
     }
 
+    /// Returns the current state, which used to be the field `state` before version 0.8.
+    pub fn state(&self) -> &T {
+        &self.search.state
+    }
+
     /// Calculates the entropy of a choice.
     pub fn entropy(&self, i: usize) -> f64 {
-        let sum: f64 = self.weights[i].iter().sum();
-        self.weights[i].iter().map(|&w| {
-                let p: f64 = w / sum;
-                -(p * p.ln())
-            }).sum()
+        entropy(&self.weights[i])
     }
 
     /// Finds the position with least entropy.
     pub fn min_entropy<G>(&self, g: &mut G) -> Option<(usize, T::Pos)>
         where G: FnMut(&T, T::Pos) -> Vec<T::Val>
     {
-        let mut min: Option<(usize, f64)> = None;
-        for i in 0..self.weights.len() {
-            if self.weights.len() == 0 {continue};
-            if g(&self.state, self.start_choice[i].0).len() == 0 {continue};
-            let e = self.entropy(i);
-            if min.is_none() || min.unwrap().1 > e {
-                min = Some((i, e));
-            }
-        }
-        min.map(|(i, _)| (i, self.start_choice[i].0))
+        min_entropy(&self.weights, &self.start_choice, &self.search.state, g)
     }
 
     /// Increase weight of observed state.
     pub fn observe(&mut self, pos: T::Pos, new_val: T::Val)
         where T::Pos: PartialEq,
     {
-        for (i, ch) in self.start_choice.iter().enumerate() {
-            if ch.0 == pos {
-                for (j, val) in self.start_choice[i].1.iter().enumerate() {
-                    if *val == new_val {
-                        self.weights[i][j] += 1.0;
-                        return;
-                    }
-                }
-            }
-        }
+        observe(&mut self.weights, &self.start_choice, pos, new_val)
     }
 
     /// Attempts to solve puzzle repeatedly, using `SolveSettings::max_iterations`.
@@ -686,129 +1079,121 @@ impl<T> EntropyBackTrackSolver<T> where T: Puzzle {
     ///
     /// This can be called repeated times, limited by `SolveSettings::max_iterations`
     /// to reuse weights from previous attempts.
-    pub fn solve_single_attempt<G>(&mut self, mut g: G) -> Option<Solution<T>>
+    pub fn solve_single_attempt<G>(&mut self, g: G) -> Option<Solution<T>>
         where G: FnMut(&T, T::Pos) -> Vec<T::Val>,
               T::Pos: PartialEq
     {
-        use std::thread::sleep;
-        use std::time::Duration;
-
-        let mut rng = rand::rng();
+        let mut policy = EntropyPolicy {
+            weights: &mut self.weights,
+            start_choice: &self.start_choice,
+            noise: self.entropy_settings.noise,
+            rng: rand::rng(),
+            g,
+            ind: 0,
+        };
         let mut iterations: u64 = 0;
-        loop {
-            if self.settings.debug {
-                if let Some(ms) = self.settings.sleep_ms {
-                    sleep(Duration::from_millis(ms));
-                }
-            }
-            if self.settings.solve_simple {
-                let ref mut prevs = self.prevs;
-                self.state.solve_simple(|state, pos, val| {
-                    prevs.push((pos, state.get(pos), true));
-                    state.set(pos, val);
-                });
-            }
-            if self.settings.debug {
-                self.state.print();
-            }
-            iterations += 1;
-            if let Some(max_iterations) = self.settings.max_iterations {
-                if iterations > max_iterations {
-                    return None;
-                }
-            }
-            if self.state.is_solved() {
-                if self.settings.debug {
-                    eprintln!("Solved! Iterations: {}", iterations);
-                }
-                if self.settings.difference {
-                    self.state.remove(&self.original);
+        let mut clock = ProgressClock::new();
+        match drive_search(&self.settings, &mut iterations, &mut clock, &mut self.search, &mut policy, &mut ()) {
+            // The search is kept for later attempts, so the solution is a copy.
+            Stop::Solved => Some(Solution::new(self.search.state.clone(),
+                &self.original, &self.settings, iterations, None)),
+            _ => None,
+        }
+    }
+}
+
+/// Calculates the entropy of weights.
+fn entropy(weights: &[f64]) -> f64 {
+    let sum: f64 = weights.iter().sum();
+    weights.iter().map(|&w| {
+            let p: f64 = w / sum;
+            -(p * p.ln())
+        }).sum()
+}
+
+/// Finds the position with least entropy.
+fn min_entropy<T, G>(
+    weights: &[Vec<f64>],
+    start_choice: &[(T::Pos, Vec<T::Val>)],
+    state: &T,
+    g: &mut G
+) -> Option<(usize, T::Pos)>
+    where T: Puzzle, G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    let mut min: Option<(usize, f64)> = None;
+    for i in 0..weights.len() {
+        if weights.is_empty() {continue};
+        if g(state, start_choice[i].0).is_empty() {continue};
+        let e = entropy(&weights[i]);
+        if min.is_none() || min.unwrap().1 > e {
+            min = Some((i, e));
+        }
+    }
+    min.map(|(i, _)| (i, start_choice[i].0))
+}
+
+/// Increase weight of observed state.
+fn observe<P, V>(weights: &mut [Vec<f64>], start_choice: &[(P, Vec<V>)], pos: P, new_val: V)
+    where P: PartialEq, V: PartialEq
+{
+    for (i, ch) in start_choice.iter().enumerate() {
+        if ch.0 == pos {
+            for (j, val) in start_choice[i].1.iter().enumerate() {
+                if *val == new_val {
+                    weights[i][j] += 1.0;
+                    return;
                 }
-                return Some(Solution { puzzle: self.state.clone(), iterations: iterations, strategy: None });
             }
+        }
+    }
+}
 
-            let empty = self.min_entropy(&mut g);
-            let mut possible = match empty {
-                None => vec![],
-                Some((ind, x)) => {
-                    use rand::Rng;
-
-                    let mut possible = g(&self.state, x);
-                    if rng.random::<f64>() < self.entropy_settings.noise {
-                        use rand::seq::SliceRandom;
-                        possible.shuffle(&mut rng);
-                        possible
-                    } else {
-                        let mut keys = vec![];
-                        for (j, p) in possible.iter().enumerate() {
-                            for i in 0..self.start_choice[ind].1.len() {
-                                if self.start_choice[ind].1[i] == *p {
-                                    keys.push((j, self.weights[ind][i]));
-                                    break;
-                                }
-                            }
-                        }
-                        keys.sort_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap());
-                        let new_possible = keys.iter().map(|&(i, _)| possible[i]).collect::<Vec<T::Val>>();
-                        new_possible
-                    }
-                }
-            };
-            if possible.len() == 0 {
-                loop {
-                    if self.choice.len() == 0 {
-                        if self.settings.debug {
-                            // No more possible choices.
-                            eprintln!("No more possible choices");
-                        }
-                        return None;
-                    }
-                    let (pos, mut possible) = self.choice.pop().unwrap();
-                    if let Some(new_val) = possible.pop() {
-                        // Try next choice.
-                        while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
-                            self.state.set(old_pos, old_val);
-                            if !simple {break}
-                        }
-                        self.prevs.push((pos, self.state.get(pos), false));
-                        self.state.set(pos, new_val);
-                        self.observe(pos, new_val);
-                        self.choice.push((pos, possible));
-                        if self.settings.debug {
-                            eprintln!("Try   {:?}, {:?} depth ch: {} prev: {} (failed at {:?}) it: {}",
-                                pos, new_val, self.choice.len(), self.prevs.len(), empty, iterations);
-                        } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                            eprintln!("Iteration: {}mill", iterations / 1_000_000);
-                        }
+/// Picks positions with least entropy, and values by weight.
+struct EntropyPolicy<'a, T: Puzzle, G, R> {
+    weights: &'a mut Vec<Vec<f64>>,
+    start_choice: &'a [(T::Pos, Vec<T::Val>)],
+    noise: f64,
+    rng: R,
+    g: G,
+    /// The index of the last selected choice.
+    ind: usize,
+}
+
+impl<'a, T, G, R> Policy<T> for EntropyPolicy<'a, T, G, R>
+    where T: Puzzle,
+          T::Pos: PartialEq,
+          G: FnMut(&T, T::Pos) -> Vec<T::Val>,
+          R: rand::Rng
+{
+    fn select(&mut self, state: &T) -> Option<T::Pos> {
+        let (ind, pos) = min_entropy(self.weights, self.start_choice, state, &mut self.g)?;
+        self.ind = ind;
+        Some(pos)
+    }
+
+    fn order(&mut self, state: &T, pos: T::Pos) -> Vec<T::Val> {
+        let ind = self.ind;
+        let mut possible = (self.g)(state, pos);
+        if self.rng.random::<f64>() < self.noise {
+            use rand::seq::SliceRandom;
+            possible.shuffle(&mut self.rng);
+            possible
+        } else {
+            let mut keys = vec![];
+            for (j, p) in possible.iter().enumerate() {
+                for i in 0..self.start_choice[ind].1.len() {
+                    if self.start_choice[ind].1[i] == *p {
+                        keys.push((j, self.weights[ind][i]));
                         break;
-                    } else {
-                        let mut undo = false;
-                        while let Some((old_pos, old_val, simple)) = self.prevs.pop() {
-                            self.state.set(old_pos, old_val);
-                            undo = true;
-                            if !simple {break}
-                        }
-                        if !undo {
-                            // No more possible choices.
-                            return None;
-                        }
                     }
                 }
-            } else {
-                let empty = empty.unwrap().1;
-                // Put in the first guess.
-                let v = possible.pop().unwrap();
-                self.prevs.push((empty, self.state.get(empty), false));
-                self.state.set(empty, v);
-                self.observe(empty, v);
-                self.choice.push((empty, possible));
-                if self.settings.debug {
-                    eprintln!("Guess {:?}, {:?} depth ch: {} prev: {} it: {}",
-                        empty, v, self.choice.len(), self.prevs.len(), iterations);
-                } else if self.settings.print_millions && (iterations % 1_000_000 == 0) {
-                    eprintln!("Iteration: {}mill", iterations / 1_000_000);
-                }
             }
+            keys.sort_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap());
+            keys.iter().map(|&(i, _)| possible[i]).collect()
         }
     }
+
+    fn assigned(&mut self, _state: &T, pos: T::Pos, val: T::Val) {
+        observe(self.weights, self.start_choice, pos, val)
+    }
 }
@@ -0,0 +1,171 @@
+//! Min-conflicts local search.
+
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::progress::ProgressClock;
+use crate::{Puzzle, SearchStats, Solution, SolveSettings};
+
+/// Solves puzzles using min-conflicts local search.
+///
+/// Instead of building a solution one value at a time, the solver starts with
+/// a complete assignment and repeatedly picks a random position with conflicts,
+/// setting the value that leaves fewest conflicts at that position.
+/// This is often much faster than backtracking for large puzzles with many solutions,
+/// such as N-queens or scheduling, but it can not prove that there is no solution.
+///
+/// To escape local minima, the solver sometimes sets a random value instead (random walk),
+/// and recently replaced values can not be set back at the same position for a while (tabu),
+/// unless this removes all conflicts at the position.
+///
+/// The puzzle is solved when no position has conflicts and `Puzzle::is_solved` returns `true`.
+/// `SolveSettings::max_iterations` limits the number of steps, and should usually be set,
+/// since the solver never gives up otherwise.
+pub struct LocalSearchSolver<T> where T: Puzzle {
+    /// Stores the original state.
+    pub original: T,
+    /// Stores the state.
+    pub state: T,
+    /// The probability of setting a random value instead of minimizing conflicts.
+    pub noise: f64,
+    /// The number of steps a replaced value stays tabu.
+    pub tabu: usize,
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+}
+
+impl<T> LocalSearchSolver<T> where T: Puzzle, T::Pos: PartialEq {
+    /// Creates a new solver, starting from a complete assignment.
+    pub fn new(puzzle: T, settings: SolveSettings) -> LocalSearchSolver<T> {
+        LocalSearchSolver {
+            original: puzzle.clone(),
+            state: puzzle,
+            noise: 0.1,
+            tabu: 10,
+            seed: 0,
+            settings,
+        }
+    }
+
+    /// Sets the probability of setting a random value instead of minimizing conflicts.
+    pub fn set_noise(&mut self, val: f64) {
+        self.noise = val;
+    }
+
+    /// The probability of setting a random value instead of minimizing conflicts.
+    pub fn noise(mut self, val: f64) -> Self {
+        self.set_noise(val);
+        self
+    }
+
+    /// Sets the number of steps a replaced value stays tabu.
+    pub fn set_tabu(&mut self, val: usize) {
+        self.tabu = val;
+    }
+
+    /// The number of steps a replaced value stays tabu.
+    pub fn tabu(mut self, val: usize) -> Self {
+        self.set_tabu(val);
+        self
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn set_seed(&mut self, val: u64) {
+        self.seed = val;
+    }
+
+    /// The seed of the random number generator.
+    pub fn seed(mut self, val: u64) -> Self {
+        self.set_seed(val);
+        self
+    }
+
+    /// Solves puzzle, using a closure that counts conflicts at a position,
+    /// and a closure returning the values that can be set at a position.
+    ///
+    /// Only the listed positions are changed by the solver.
+    /// Ties between values with fewest conflicts are broken randomly.
+    pub fn solve<C, G>(mut self, positions: &[T::Pos], mut conflicts: C, mut g: G) -> Option<Solution<T>>
+        where C: FnMut(&T, T::Pos) -> usize,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut tabu: VecDeque<(T::Pos, T::Val)> = VecDeque::new();
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        loop {
+            if self.settings.debug {
+                if let Some(ms) = self.settings.sleep_ms {
+                    sleep(Duration::from_millis(ms));
+                }
+                self.settings.trace_state(&self.state);
+            }
+
+            let conflicted: Vec<T::Pos> = positions.iter().cloned()
+                .filter(|&pos| conflicts(&self.state, pos) > 0).collect();
+            if conflicted.is_empty() && self.state.is_solved() {
+                if self.settings.debug {
+                    self.settings.trace(format_args!("Solved! Iterations: {}", iterations));
+                }
+                if self.settings.difference {
+                    self.state.remove(&self.original);
+                }
+                return Some(Solution {puzzle: self.state, iterations, strategy: None, stats: SearchStats::default()});
+            }
+            if conflicted.is_empty() {
+                if self.settings.debug {
+                    self.settings.trace(format_args!("No conflicts, but puzzle is not solved"));
+                }
+                return None;
+            }
+
+            iterations += 1;
+            if self.settings.max_iterations.is_some_and(|max| iterations > max) {
+                return None;
+            }
+            self.settings.report_progress(&mut clock, iterations, || (0, None));
+
+            let pos = conflicted[rng.random_range(0..conflicted.len())];
+            let old = self.state.get(pos);
+            let possible: Vec<T::Val> = g(&self.state, pos).into_iter().filter(|&v| v != old).collect();
+            if possible.is_empty() {continue};
+
+            let new_val = if rng.random::<f64>() < self.noise {
+                // Random walk.
+                possible[rng.random_range(0..possible.len())]
+            } else {
+                // The old value is kept if picked among the best values.
+                let mut best: Vec<T::Val> = vec![old];
+                let mut min = conflicts(&self.state, pos);
+                for &v in &possible {
+                    self.state.set(pos, v);
+                    let c = conflicts(&self.state, pos);
+                    if c > 0 && tabu.contains(&(pos, v)) {continue};
+                    if c < min {
+                        min = c;
+                        best.clear();
+                    }
+                    if c == min {best.push(v)};
+                }
+                self.state.set(pos, old);
+                let v = best[rng.random_range(0..best.len())];
+                if v == old {continue};
+                v
+            };
+            self.state.set(pos, new_val);
+            tabu.push_back((pos, old));
+            while tabu.len() > self.tabu {tabu.pop_front();}
+            if self.settings.debug {
+                self.settings.trace(format_args!("Set   {:?}, {:?} (was {:?}) conflicted: {} it: {}",
+                    pos, new_val, old, conflicted.len(), iterations));
+            }
+        }
+    }
+}
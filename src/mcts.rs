@@ -0,0 +1,317 @@
+//! Monte Carlo tree search.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::progress::ProgressClock;
+use crate::{Puzzle, SearchStats, Solution, SolveSettings};
+
+/// Stores a node in the search tree.
+struct Node<P, V> {
+    /// The move from the parent.
+    mv: Option<(P, V)>,
+    parent: usize,
+    children: Vec<usize>,
+    /// The position of the moves from this node.
+    pos: Option<P>,
+    /// The values not yet expanded, or `None` if the moves are not generated yet.
+    untried: Option<Vec<V>>,
+    visits: u64,
+    reward: f64,
+    /// Whether the subtree is fully explored without finding a solution.
+    dead: bool,
+}
+
+/// Solves puzzles using Monte Carlo tree search.
+///
+/// This is useful for puzzles with few solutions, where heuristics are weak
+/// but random playouts tell which choices lead deep into the search tree.
+///
+/// Each iteration walks down the tree of moves, picking children by upper confidence bound (UCT),
+/// expands one untried value, and plays random moves from there until the puzzle
+/// is solved or no values are possible. The reward of a playout is the number of moves made,
+/// relative to the longest playout so far, so choices that leave the puzzle open longer
+/// are visited more often.
+///
+/// The tree is kept between iterations. Subtrees that are fully explored without a solution
+/// are never visited again, so the solver finds a solution if any exists,
+/// given enough iterations. `SolveSettings::max_iterations` limits the number of playouts.
+pub struct MonteCarloSolver<T> where T: Puzzle {
+    /// Stores the original state.
+    pub original: T,
+    /// The exploration constant of the upper confidence bound.
+    pub exploration: f64,
+    /// The seed of the random number generator.
+    pub seed: u64,
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+}
+
+impl<T> MonteCarloSolver<T> where T: Puzzle {
+    /// Creates a new solver.
+    pub fn new(puzzle: T, settings: SolveSettings) -> MonteCarloSolver<T> {
+        MonteCarloSolver {
+            original: puzzle,
+            exploration: ::std::f64::consts::SQRT_2,
+            seed: 0,
+            settings,
+        }
+    }
+
+    /// Sets the exploration constant of the upper confidence bound.
+    pub fn set_exploration(&mut self, val: f64) {
+        self.exploration = val;
+    }
+
+    /// The exploration constant of the upper confidence bound.
+    pub fn exploration(mut self, val: f64) -> Self {
+        self.set_exploration(val);
+        self
+    }
+
+    /// Sets the seed of the random number generator.
+    pub fn set_seed(&mut self, val: u64) {
+        self.seed = val;
+    }
+
+    /// The seed of the random number generator.
+    pub fn seed(mut self, val: u64) -> Self {
+        self.set_seed(val);
+        self
+    }
+
+    /// Solves puzzle, using a closure to look for best position to set a value next,
+    /// and a closure for picking options in preferred order.
+    ///
+    /// The tree expands the last value in the list first,
+    /// while playouts pick values at random.
+    pub fn solve<F, G>(self, mut f: F, mut g: G) -> Option<Solution<T>>
+        where F: FnMut(&T) -> Option<T::Pos>,
+              G: FnMut(&T, T::Pos) -> Vec<T::Val>
+    {
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut root = self.original.clone();
+        self.simple(&mut root);
+        let mut nodes: Vec<Node<T::Pos, T::Val>> = vec![Node {
+            mv: None,
+            parent: 0,
+            children: vec![],
+            pos: None,
+            untried: None,
+            visits: 0,
+            reward: 0.0,
+            dead: false,
+        }];
+        let mut longest: u64 = 1;
+        let mut iterations: u64 = 0;
+        let mut clock = ProgressClock::new();
+        loop {
+            if nodes[0].dead {
+                if self.settings.debug {
+                    self.settings.trace(format_args!("No more possible choices"));
+                }
+                return None;
+            }
+            iterations += 1;
+            if self.settings.max_iterations.is_some_and(|max| iterations > max) {
+                return None;
+            }
+            self.settings.report_progress(&mut clock, iterations, || (0, None));
+
+            // Select a node to expand.
+            let mut state = root.clone();
+            let mut node = 0;
+            let mut depth: u64 = 0;
+            loop {
+                if state.is_solved() {
+                    return Some(self.solution(state, iterations));
+                }
+                if nodes[node].untried.is_none() {
+                    let pos = f(&state);
+                    let possible = match pos {
+                        None => vec![],
+                        Some(pos) => g(&state, pos),
+                    };
+                    nodes[node].pos = pos;
+                    nodes[node].untried = Some(possible);
+                }
+                // Expand the preferred value first.
+                if let Some(v) = nodes[node].untried.as_mut().unwrap().pop() {
+                    let pos = nodes[node].pos.unwrap();
+                    let child = nodes.len();
+                    nodes.push(Node {
+                        mv: Some((pos, v)),
+                        parent: node,
+                        children: vec![],
+                        pos: None,
+                        untried: None,
+                        visits: 0,
+                        reward: 0.0,
+                        dead: false,
+                    });
+                    nodes[node].children.push(child);
+                    self.apply(&mut state, pos, v);
+                    node = child;
+                    depth += 1;
+                    break;
+                }
+                let parent_visits = nodes[node].visits.max(1) as f64;
+                let mut best: Option<(usize, f64)> = None;
+                for &child in &nodes[node].children {
+                    let n = &nodes[child];
+                    if n.dead {continue};
+                    let score = if n.visits == 0 {f64::INFINITY} else {
+                        n.reward / n.visits as f64 +
+                        self.exploration * (parent_visits.ln() / n.visits as f64).sqrt()
+                    };
+                    if best.is_none_or(|(_, s)| s < score) {
+                        best = Some((child, score));
+                    }
+                }
+                match best {
+                    None => {
+                        // All children are explored.
+                        mark_dead(&mut nodes, node);
+                        break;
+                    }
+                    Some((child, _)) => {
+                        let (pos, v) = nodes[child].mv.unwrap();
+                        self.apply(&mut state, pos, v);
+                        node = child;
+                        depth += 1;
+                    }
+                }
+            }
+            if nodes[node].dead {continue};
+
+            // Play random moves until solved or stuck.
+            let mut playout = state.clone();
+            let mut moves = 0;
+            loop {
+                if playout.is_solved() {
+                    return Some(self.solution(playout, iterations));
+                }
+                let possible = match f(&playout) {
+                    None => vec![],
+                    Some(pos) => g(&playout, pos).into_iter().map(|v| (pos, v)).collect(),
+                };
+                if possible.is_empty() {
+                    if moves == 0 {
+                        // The expanded node has no moves.
+                        nodes[node].untried = Some(vec![]);
+                        mark_dead(&mut nodes, node);
+                    }
+                    break;
+                }
+                let (pos, v) = possible[rng.random_range(0..possible.len())];
+                self.apply(&mut playout, pos, v);
+                moves += 1;
+            }
+
+            // Propagate the reward up to the root.
+            let total = depth + moves;
+            longest = longest.max(total);
+            let reward = total as f64 / longest as f64;
+            let mut n = node;
+            loop {
+                nodes[n].visits += 1;
+                nodes[n].reward += reward;
+                if n == 0 {break};
+                n = nodes[n].parent;
+            }
+
+            if self.settings.debug {
+                if let Some(ms) = self.settings.sleep_ms {
+                    sleep(Duration::from_millis(ms));
+                }
+                self.settings.trace(format_args!("Playout depth: {} moves: {} longest: {} nodes: {} it: {}",
+                    depth, moves, longest, nodes.len(), iterations));
+                self.settings.trace_state(&playout);
+            }
+        }
+    }
+
+    fn apply(&self, state: &mut T, pos: T::Pos, val: T::Val) {
+        state.set(pos, val);
+        self.simple(state);
+    }
+
+    fn simple(&self, state: &mut T) {
+        if self.settings.solve_simple {
+            state.solve_simple(|state, pos, val| state.set(pos, val));
+        }
+    }
+
+    fn solution(&self, mut state: T, iterations: u64) -> Solution<T> {
+        if self.settings.debug {
+            self.settings.trace(format_args!("Solved! Iterations: {}", iterations));
+        }
+        if self.settings.difference {
+            state.remove(&self.original);
+        }
+        Solution {puzzle: state, iterations, strategy: None, stats: SearchStats::default()}
+    }
+}
+
+/// Marks a node as dead, and its ancestors if all their moves are dead.
+fn mark_dead<P, V>(nodes: &mut [Node<P, V>], mut node: usize) {
+    loop {
+        nodes[node].dead = true;
+        if node == 0 {break};
+        node = nodes[node].parent;
+        let n = &nodes[node];
+        let expanded = n.untried.as_ref().is_some_and(|u| u.is_empty());
+        if !expanded || n.children.iter().any(|&c| !nodes[c].dead) {break};
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::testing::Queens;
+
+    fn queens(n: usize, seed: u64, settings: SolveSettings) -> Option<Solution<Queens>> {
+        MonteCarloSolver::new(Queens::new(n), settings).seed(seed).solve(Queens::first_empty, Queens::values)
+    }
+
+    #[test]
+    fn solves_queens() {
+        for seed in 0..5 {
+            assert!(queens(8, seed, SolveSettings::new()).unwrap().puzzle.is_valid());
+        }
+    }
+
+    #[test]
+    fn no_solution() {
+        assert!(queens(3, 0, SolveSettings::new()).is_none());
+    }
+
+    #[test]
+    fn max_iterations() {
+        let iterations = queens(8, 1, SolveSettings::new()).unwrap().iterations;
+        assert!(iterations > 1);
+        assert!(queens(8, 1, SolveSettings::new().max_iterations(iterations - 1)).is_none());
+        assert!(queens(8, 1, SolveSettings::new().max_iterations(iterations)).is_some());
+    }
+
+    #[test]
+    fn reuses_tree() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let sink = lines.clone();
+        let settings = SolveSettings::new().debug(true)
+            .sink(move |line: &str| sink.lock().unwrap().push(line.to_string()));
+        assert!(queens(3, 0, settings).is_none());
+        let lines = lines.lock().unwrap();
+        // The tree of 3 queens has 5 moves, so every playout adds a node
+        // until all moves are explored.
+        let nodes: Vec<&str> = lines.iter().filter_map(|line| line.split("nodes: ").nth(1))
+            .map(|rest| rest.split(' ').next().unwrap()).collect();
+        assert_eq!(nodes, vec!["2", "3", "4", "5", "6"]);
+        assert_eq!(lines.last().unwrap(), "No more possible choices");
+    }
+}
@@ -0,0 +1,267 @@
+//! Nogood learning.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use fnv::FnvHashMap;
+
+use crate::{Puzzle, SearchHooks};
+
+/// Stores failed combinations of guesses, called "nogoods".
+///
+/// When the solver runs out of values at a position, the guesses made before
+/// can not be part of a solution together. The store remembers such combinations
+/// and prunes a value when all other guesses of a nogood hold in the current state.
+///
+/// The store is bounded by a capacity, removing the oldest nogood first.
+/// It can be reused across restarts, as long as the puzzle being solved is the same.
+///
+/// Use it with `BackTrackSolver::solve_with_hooks`.
+pub struct NogoodStore<P, V> {
+    nogoods: FnvHashMap<u64, Vec<(P, V)>>,
+    index: FnvHashMap<(P, V), Vec<u64>>,
+    order: VecDeque<u64>,
+    next_id: u64,
+    /// The maximum number of nogoods to store.
+    pub capacity: usize,
+    /// The maximum number of guesses in a stored nogood.
+    pub max_len: usize,
+    /// The number of values pruned.
+    pub pruned: u64,
+}
+
+impl<P, V> NogoodStore<P, V>
+    where P: Copy + Hash + Eq, V: Copy + Hash + Eq
+{
+    /// Creates a new nogood store with a maximum number of nogoods.
+    pub fn new(capacity: usize) -> NogoodStore<P, V> {
+        NogoodStore {
+            nogoods: FnvHashMap::default(),
+            index: FnvHashMap::default(),
+            order: VecDeque::new(),
+            next_id: 0,
+            capacity,
+            max_len: usize::MAX,
+            pruned: 0,
+        }
+    }
+
+    /// Sets the maximum number of guesses in a stored nogood.
+    pub fn set_max_len(&mut self, val: usize) {
+        self.max_len = val;
+    }
+
+    /// The maximum number of guesses in a stored nogood.
+    ///
+    /// Long nogoods rarely match again, so limiting them saves memory and time.
+    pub fn max_len(mut self, val: usize) -> Self {
+        self.set_max_len(val);
+        self
+    }
+
+    /// Returns the number of stored nogoods.
+    pub fn len(&self) -> usize {
+        self.nogoods.len()
+    }
+
+    /// Returns `true` if there are no stored nogoods.
+    pub fn is_empty(&self) -> bool {
+        self.nogoods.is_empty()
+    }
+
+    /// Removes all nogoods.
+    pub fn clear(&mut self) {
+        self.nogoods.clear();
+        self.index.clear();
+        self.order.clear();
+    }
+
+    /// Iterates over stored nogoods, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &[(P, V)]> {
+        self.order.iter().map(move |id| &self.nogoods[id][..])
+    }
+
+    /// Adds a nogood, unless it is empty, too long or already stored.
+    ///
+    /// Returns `true` if the nogood was added.
+    pub fn add(&mut self, nogood: Vec<(P, V)>) -> bool {
+        if nogood.is_empty() || nogood.len() > self.max_len || self.capacity == 0 {
+            return false;
+        }
+        if self.contains(&nogood) {return false};
+
+        while self.nogoods.len() >= self.capacity {
+            self.remove_oldest();
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        for &lit in &nogood {
+            self.index.entry(lit).or_default().push(id);
+        }
+        self.nogoods.insert(id, nogood);
+        self.order.push_back(id);
+        true
+    }
+
+    /// Returns `true` if the same combination is stored, in any order.
+    pub fn contains(&self, nogood: &[(P, V)]) -> bool {
+        let ids = match nogood.first().and_then(|lit| self.index.get(lit)) {
+            None => return false,
+            Some(ids) => ids,
+        };
+        ids.iter().any(|id| {
+            let other = &self.nogoods[id];
+            other.len() == nogood.len() && nogood.iter().all(|lit| other.contains(lit))
+        })
+    }
+
+    /// Returns `true` if setting a value at a position completes a nogood,
+    /// given the values in the state.
+    pub fn is_nogood<T>(&self, state: &T, pos: P, val: V) -> bool
+        where T: Puzzle<Pos = P, Val = V>
+    {
+        let ids = match self.index.get(&(pos, val)) {
+            None => return false,
+            Some(ids) => ids,
+        };
+        ids.iter().any(|id| {
+            self.nogoods[id].iter().all(|&(p, v)| p == pos || state.get(p) == v)
+        })
+    }
+
+    fn remove_oldest(&mut self) {
+        let id = match self.order.pop_front() {
+            None => return,
+            Some(id) => id,
+        };
+        if let Some(nogood) = self.nogoods.remove(&id) {
+            for lit in &nogood {
+                let empty = if let Some(ids) = self.index.get_mut(lit) {
+                    ids.retain(|&i| i != id);
+                    ids.is_empty()
+                } else {false};
+                if empty {
+                    self.index.remove(lit);
+                }
+            }
+        }
+    }
+}
+
+impl<T> SearchHooks<T> for NogoodStore<T::Pos, T::Val>
+    where T: Puzzle, T::Pos: Hash + Eq, T::Val: Hash + Eq
+{
+    fn prune(&mut self, state: &T, pos: T::Pos, val: T::Val) -> bool {
+        if self.is_nogood(state, pos, val) {
+            self.pruned += 1;
+            true
+        } else {false}
+    }
+
+    fn dead_end(&mut self, state: &T, choice: &[(T::Pos, Vec<T::Val>)]) {
+        self.add(choice.iter().map(|&(pos, _)| (pos, state.get(pos))).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+    use crate::{BackTrackSolver, Puzzle, SolveOutcome, SolveSettings};
+
+    fn puzzle() -> Row {Row::new(4, 3, 8)}
+
+    fn all() -> Vec<Vec<u8>> {
+        puzzle().solutions(SolveSettings::new(), &mut ()).0
+    }
+
+    #[test]
+    fn pruning_keeps_solutions() {
+        let mut store = NogoodStore::new(1000);
+        assert_eq!(puzzle().solutions(SolveSettings::new(), &mut store).0, all());
+        assert!(!store.is_empty());
+    }
+
+    #[test]
+    fn reuse_across_restarts() {
+        let mut store = NogoodStore::new(1000);
+        for _ in 0..3 {
+            assert_eq!(puzzle().solutions(SolveSettings::new(), &mut store).0, all());
+        }
+        assert!(store.pruned > 0);
+    }
+
+    #[test]
+    fn learned_nogood_prunes_branch() {
+        // The only solution is [2, 2], so every state starting with 1 fails.
+        let mut store = NogoodStore::new(1000);
+        let solve = |store: &mut NogoodStore<usize, u8>| {
+            BackTrackSolver::new(Row::new(2, 2, 4), SolveSettings::new())
+                .solve_with_hooks(Row::first_empty, Row::values, store).unwrap()
+        };
+        let first = solve(&mut store);
+        assert!(store.contains(&[(0, 1)]));
+        assert!(store.is_nogood(&Row::new(2, 2, 4), 0, 1));
+        assert!(!store.is_nogood(&Row::new(2, 2, 4), 0, 2));
+        assert_eq!(store.pruned, 0);
+
+        let second = solve(&mut store);
+        assert_eq!(second.puzzle.cells, vec![2, 2]);
+        // Value 1 is pruned at the first cell, and after 2 at the second cell.
+        assert_eq!(store.pruned, 2);
+        assert!(second.iterations < first.iterations);
+    }
+
+    #[test]
+    fn nogood_needs_all_other_guesses() {
+        let mut store = NogoodStore::new(10);
+        assert!(store.add(vec![(0, 1), (1, 2)]));
+        assert!(!store.add(vec![(1, 2), (0, 1)]));
+        let mut row = Row::new(3, 3, 9);
+        assert!(!store.is_nogood(&row, 1, 2));
+        row.set(0, 1);
+        assert!(store.is_nogood(&row, 1, 2));
+        assert!(!store.is_nogood(&row, 1, 1));
+    }
+
+    #[test]
+    fn max_len() {
+        let mut store = NogoodStore::new(1000).max_len(1);
+        assert!(!store.add(vec![(0, 1), (1, 1)]));
+        BackTrackSolver::new(Row::new(2, 2, 4), SolveSettings::new())
+            .solve_with_hooks(Row::first_empty, Row::values, &mut store).unwrap();
+        let stored: Vec<&[(usize, u8)]> = store.iter().collect();
+        assert_eq!(stored, vec![&[(0, 1)][..]]);
+    }
+
+    #[test]
+    fn small_capacity_and_max_len() {
+        let mut store = NogoodStore::new(3).max_len(2);
+        for _ in 0..2 {
+            assert_eq!(puzzle().solutions(SolveSettings::new(), &mut store).0, all());
+        }
+        assert!(store.len() <= 3);
+        assert!(store.iter().all(|nogood| nogood.len() <= 2));
+    }
+
+    #[test]
+    fn max_depth_does_not_learn_cut_states() {
+        let mut store = NogoodStore::new(1000);
+        for depth in 0..4 {
+            let (_, outcome) = puzzle().solutions(SolveSettings::new().max_depth(depth), &mut store);
+            assert!(matches!(outcome, SolveOutcome::DepthLimited));
+        }
+        assert_eq!(puzzle().solutions(SolveSettings::new(), &mut store).0, all());
+    }
+
+    #[test]
+    fn iterative_deepening_keeps_solutions() {
+        let mut store = NogoodStore::new(1000);
+        for _ in 0..2 {
+            let solver = BackTrackSolver::new(puzzle(), SolveSettings::new());
+            let outcome = solver.iterative_deepening(Row::first_empty, Row::values, &mut store);
+            assert_eq!(outcome.solution().unwrap().puzzle.cells, all()[0]);
+        }
+        assert_eq!(puzzle().solutions(SolveSettings::new(), &mut store).0, all());
+    }
+}
@@ -0,0 +1,236 @@
+//! Heuristics for ordering values.
+//!
+//! Each function in this module wraps a closure returning possible values at a position,
+//! and returns a new closure with the values in another order.
+//! The result can be passed as the second closure of a solver.
+//!
+//! Like the solvers, the last value in the list has highest priority.
+//! When values are equally good, the order of the wrapped closure is kept.
+
+use std::hash::Hash;
+
+use fnv::FnvHashMap;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use crate::{Puzzle, Variables};
+
+/// Counts the number of candidates left at neighbors after setting each value.
+///
+/// Returns `None` for values that leave some neighbor without candidates.
+fn neighbor_candidates<T, G>(g: &mut G, state: &T, pos: T::Pos, vals: &[T::Val]) -> Vec<Option<usize>>
+    where T: Variables, G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    let mut neighbors = state.neighbors(pos);
+    if neighbors.is_empty() {neighbors = state.unassigned()};
+    let before: Vec<usize> = neighbors.iter().map(|&n| g(state, n).len()).collect();
+    vals.iter().map(|&val| {
+        let mut experiment = state.clone();
+        experiment.set(pos, val);
+        let mut sum = 0;
+        for (i, &n) in neighbors.iter().enumerate() {
+            if experiment.get(n) != state.get(n) {continue};
+            let k = g(&experiment, n).len();
+            if k == 0 && before[i] > 0 {return None};
+            sum += k;
+        }
+        Some(sum)
+    }).collect()
+}
+
+/// Sorts values by keys, such that the largest key is last.
+fn sort_by_keys<V: Copy, K: Ord + Copy>(vals: Vec<V>, keys: &[K]) -> Vec<V> {
+    let mut inds: Vec<usize> = (0..vals.len()).collect();
+    inds.sort_by_key(|&i| keys[i]);
+    inds.into_iter().map(|i| vals[i]).collect()
+}
+
+/// Tries the value that leaves most candidates at neighbors first (least constraining value).
+///
+/// The neighbors are given by `Variables::neighbors`,
+/// or all unassigned positions if the puzzle does not list neighbors.
+/// Values that leave a neighbor without candidates are tried last.
+pub fn least_constraining<T, G>(mut g: G) -> impl FnMut(&T, T::Pos) -> Vec<T::Val>
+    where T: Variables, G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    move |state: &T, pos: T::Pos| {
+        let vals = g(state, pos);
+        if vals.len() <= 1 {return vals};
+        let keys = neighbor_candidates(&mut g, state, pos, &vals);
+        sort_by_keys(vals, &keys)
+    }
+}
+
+/// Tries the value that leaves fewest candidates at neighbors first (most constraining value).
+///
+/// This is useful to find out quickly that a position has no solution.
+/// Values that leave a neighbor without candidates are tried first.
+pub fn most_constraining<T, G>(mut g: G) -> impl FnMut(&T, T::Pos) -> Vec<T::Val>
+    where T: Variables, G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    move |state: &T, pos: T::Pos| {
+        let vals = g(state, pos);
+        if vals.len() <= 1 {return vals};
+        let keys: Vec<_> = neighbor_candidates(&mut g, state, pos, &vals).into_iter()
+            .map(|k| k.map(|k| usize::MAX - k).unwrap_or(usize::MAX)).collect();
+        sort_by_keys(vals, &keys)
+    }
+}
+
+/// Tries values in random order, using a seed for the random number generator.
+pub fn random<T, G>(seed: u64, mut g: G) -> impl FnMut(&T, T::Pos) -> Vec<T::Val>
+    where T: Puzzle, G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    move |state: &T, pos: T::Pos| {
+        let mut vals = g(state, pos);
+        vals.shuffle(&mut rng);
+        vals
+    }
+}
+
+/// Tries values that were most frequent in past solutions first.
+///
+/// Frequencies are counted per position and value.
+pub struct Frequency<P, V> {
+    /// Stores the number of times a value was seen at a position.
+    pub counts: FnvHashMap<(P, V), u64>,
+}
+
+impl<P, V> Default for Frequency<P, V> {
+    fn default() -> Frequency<P, V> {
+        Frequency {counts: FnvHashMap::default()}
+    }
+}
+
+impl<P, V> Frequency<P, V>
+    where P: Copy + Hash + Eq, V: Copy + Hash + Eq
+{
+    /// Creates a new value ordering without any past solutions.
+    pub fn new() -> Frequency<P, V> {
+        Frequency::default()
+    }
+
+    /// Increases the count of a value at a position.
+    pub fn observe(&mut self, pos: P, val: V) {
+        *self.counts.entry((pos, val)).or_insert(0) += 1;
+    }
+
+    /// Learns the values of a solution at some positions.
+    ///
+    /// The positions are usually the unassigned positions of the original puzzle.
+    pub fn learn<T, I>(&mut self, solution: &T, positions: I)
+        where T: Puzzle<Pos = P, Val = V>, I: IntoIterator<Item = P>
+    {
+        for pos in positions {
+            self.observe(pos, solution.get(pos));
+        }
+    }
+
+    /// Gets the number of times a value was seen at a position.
+    pub fn count(&self, pos: P, val: V) -> u64 {
+        self.counts.get(&(pos, val)).cloned().unwrap_or(0)
+    }
+
+    /// Sorts values such that the most frequent value is last.
+    pub fn sort(&self, pos: P, vals: Vec<V>) -> Vec<V> {
+        let keys: Vec<u64> = vals.iter().map(|&v| self.count(pos, v)).collect();
+        sort_by_keys(vals, &keys)
+    }
+
+    /// Wraps a closure returning possible values, sorting them by frequency.
+    pub fn wrap<'a, T, G>(&'a self, mut g: G) -> impl FnMut(&T, P) -> Vec<V> + 'a
+        where T: Puzzle<Pos = P, Val = V>, G: FnMut(&T, P) -> Vec<V> + 'a
+    {
+        move |state: &T, pos: P| self.sort(pos, g(state, pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::propagate::Domain;
+    use crate::{select, BackTrackSolver, BitDomains, Domains, SearchHooks, SolveSettings};
+
+    /// Setting position 0 to a value removes it from positions 1, 2 and 3.
+    ///
+    /// Setting 1 leaves position 3 without candidates, setting 0 leaves 5 candidates
+    /// at the neighbors and setting 2 leaves 6.
+    fn state() -> BitDomains {
+        let peers = vec![vec![1, 2, 3], vec![0], vec![0], vec![0]];
+        BitDomains::with_peers(vec![
+            Domain::range(0, 3),
+            Domain::range(0, 2),
+            Domain::range(0, 4),
+            Domain::singleton(1),
+        ], Arc::new(peers))
+    }
+
+    struct FirstGuess(Option<Option<u32>>);
+
+    impl SearchHooks<BitDomains> for FirstGuess {
+        fn guess(&mut self, _state: &BitDomains, _pos: usize, val: Option<u32>, _depth: usize) {
+            self.0.get_or_insert(val);
+        }
+    }
+
+    /// Returns the order of values at position 0 and the value the solver tries first.
+    ///
+    /// Each of them uses a new closure, so closures with state start the same way.
+    fn order<F, G>(new: F) -> (Vec<Option<u32>>, Option<u32>)
+        where F: Fn() -> G, G: FnMut(&BitDomains, usize) -> Vec<Option<u32>>
+    {
+        let vals = new()(&state(), 0);
+        let mut first = FirstGuess(None);
+        let solver = BackTrackSolver::new(state(), SolveSettings::new());
+        solver.solve_with_hooks(select::first_unassigned, new(), &mut first);
+        (vals, first.0.unwrap())
+    }
+
+    #[test]
+    fn least_constraining_last() {
+        let (vals, first) = order(|| least_constraining(BitDomains::candidates));
+        assert_eq!(vals, vec![Some(1), Some(0), Some(2)]);
+        assert_eq!(first, Some(2));
+    }
+
+    #[test]
+    fn most_constraining_last() {
+        let (vals, first) = order(|| most_constraining(BitDomains::candidates));
+        assert_eq!(vals, vec![Some(2), Some(0), Some(1)]);
+        assert_eq!(first, Some(1));
+    }
+
+    #[test]
+    fn random_last() {
+        let (vals, first) = order(|| random(7, BitDomains::candidates));
+        let (again, _) = order(|| random(7, BitDomains::candidates));
+        assert_eq!(vals, again);
+        assert_eq!(first, *vals.last().unwrap());
+        let mut sorted = vals.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn frequency_last() {
+        let mut frequency = Frequency::new();
+        frequency.observe(0, Some(1));
+        frequency.observe(0, Some(1));
+        frequency.observe(0, Some(2));
+        let (vals, first) = order(|| frequency.wrap(BitDomains::candidates));
+        assert_eq!(vals, vec![Some(0), Some(2), Some(1)]);
+        assert_eq!(first, Some(1));
+    }
+
+    #[test]
+    fn ties_keep_order() {
+        let frequency = Frequency::new();
+        let (vals, first) = order(|| frequency.wrap(BitDomains::candidates));
+        assert_eq!(vals, state().candidates(0));
+        assert_eq!(first, Some(0));
+    }
+}
@@ -0,0 +1,170 @@
+//! Progress reporting.
+
+use std::time::{Duration, Instant};
+
+/// Receives progress reports from solvers.
+pub type ProgressFn = Box<dyn Fn(&Progress) + Send + Sync>;
+
+/// Tells how often progress is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// Report every number of iterations.
+    Iterations(u64),
+    /// Report every time interval.
+    Time(Duration),
+}
+
+/// Stores a progress report of a running search.
+#[derive(Clone, Copy, Debug)]
+pub struct Progress {
+    /// The number of iterations so far.
+    pub iterations: u64,
+    /// The number of values set by choices in the current state,
+    /// or zero for solvers that do not keep a current state.
+    pub depth: usize,
+    /// The estimated fraction of the search tree explored, from 0 to 1.
+    ///
+    /// This is `None` for solvers that do not explore a search tree in depth first order.
+    pub explored: Option<f64>,
+    /// The time since the search started.
+    pub elapsed: Duration,
+    /// The estimated time until the whole search tree is explored.
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    /// Estimates the remaining time from the elapsed time and the fraction explored.
+    fn eta(elapsed: Duration, explored: Option<f64>) -> Option<Duration> {
+        match explored {
+            Some(f) if f > 0.0 && f <= 1.0 =>
+                Duration::try_from_secs_f64(elapsed.as_secs_f64() * (1.0 - f) / f).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Keeps track of time and iterations between progress reports.
+///
+/// There is one clock per solve, even when the solve runs several searches.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProgressClock {
+    start: Option<Instant>,
+    last: Option<Instant>,
+    last_iterations: u64,
+}
+
+impl ProgressClock {
+    /// Creates a new clock, which starts at the first report.
+    pub(crate) fn new() -> ProgressClock {
+        ProgressClock::default()
+    }
+
+    /// Returns a report if one is due.
+    ///
+    /// The closure returns the depth and the fraction explored, and is only called for a report.
+    pub(crate) fn report<F>(
+        &mut self,
+        interval: ProgressInterval,
+        iterations: u64,
+        progress: F
+    ) -> Option<Progress>
+        where F: FnOnce() -> (usize, Option<f64>)
+    {
+        let now = Instant::now();
+        let start = *self.start.get_or_insert(now);
+        let last = *self.last.get_or_insert(now);
+        let due = match interval {
+            ProgressInterval::Iterations(n) => iterations >= self.last_iterations.saturating_add(n.max(1)),
+            ProgressInterval::Time(d) => now - last >= d,
+        };
+        if !due {return None};
+        self.last = Some(now);
+        self.last_iterations = iterations;
+        let elapsed = now - start;
+        let (depth, explored) = progress();
+        Some(Progress {
+            iterations,
+            depth,
+            explored,
+            elapsed,
+            eta: Progress::eta(elapsed, explored),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::testing::Row;
+    use crate::{BackTrackSolver, MultiBackTrackSolver, SolveSettings};
+
+    /// Returns settings that collect the iterations of progress reports.
+    fn collect(interval: u64) -> (SolveSettings, Arc<Mutex<Vec<u64>>>) {
+        let reports = Arc::new(Mutex::new(vec![]));
+        let sink = reports.clone();
+        let settings = SolveSettings::new()
+            .progress_interval(ProgressInterval::Iterations(interval))
+            .on_progress(move |p: &Progress| sink.lock().unwrap().push(p.iterations));
+        (settings, reports)
+    }
+
+    #[test]
+    fn every_iterations() {
+        let mut clock = ProgressClock::new();
+        let reports: Vec<u64> = (1..=100)
+            .filter_map(|i| clock.report(ProgressInterval::Iterations(20), i, || (0, None)))
+            .map(|p| p.iterations).collect();
+        assert_eq!(reports, vec![20, 40, 60, 80, 100]);
+    }
+
+    #[test]
+    fn one_clock_per_solve() {
+        // No solution, so the whole tree is explored.
+        let (settings, reports) = collect(20);
+        let solver = BackTrackSolver::new(Row::new(4, 3, 13), settings);
+        assert!(solver.solve(Row::first_empty, Row::values).is_none());
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() > 1);
+        assert!(reports.iter().enumerate().all(|(i, &n)| n == 20 * (i as u64 + 1)));
+
+        // Several strategies still report once per interval.
+        let (settings, reports) = collect(20);
+        let solver = MultiBackTrackSolver::new(settings);
+        let strategies: &[(fn(&_) -> _, fn(&_, _) -> _)] = &[
+            (Row::first_empty, Row::values),
+            (Row::first_empty, Row::values),
+        ];
+        assert!(solver.solve(Row::new(4, 3, 13), strategies).is_none());
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() > 1);
+        assert!(reports.iter().enumerate().all(|(i, &n)| n == 20 * (i as u64 + 1)));
+    }
+
+    #[test]
+    fn print_millions() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let sink = lines.clone();
+        let settings = SolveSettings::new().print_millions(true)
+            .sink(move |line: &str| sink.lock().unwrap().push(line.to_string()));
+        let mut clock = ProgressClock::new();
+        for i in 1..=2_500_000 {
+            settings.report_progress(&mut clock, i, || (0, None));
+        }
+        assert_eq!(*lines.lock().unwrap(), vec!["Iteration: 1mill", "Iteration: 2mill"]);
+
+        // Progress reports replace the printing.
+        let (settings, reports) = collect(1_000_000);
+        let lines = Arc::new(Mutex::new(vec![]));
+        let sink = lines.clone();
+        let settings = settings.print_millions(true)
+            .sink(move |line: &str| sink.lock().unwrap().push(line.to_string()));
+        let mut clock = ProgressClock::new();
+        for i in 1..=2_500_000 {
+            settings.report_progress(&mut clock, i, || (0, None));
+        }
+        assert!(lines.lock().unwrap().is_empty());
+        assert_eq!(*reports.lock().unwrap(), vec![1_000_000, 2_000_000]);
+    }
+}
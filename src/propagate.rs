@@ -0,0 +1,591 @@
+//! Constraint propagation.
+//!
+//! A constraint problem is modeled as variables with domains of small integer values,
+//! and propagators that remove values from domains which can not be part of a solution.
+//! Each propagator watches some variables and is woken up when one of them changes,
+//! until no more values can be removed (AC-3 style).
+//!
+//! The `Csp` puzzle uses domains as values, so when propagation runs in `solve_simple`
+//! each reduced domain is recorded by the solver like any other simple move.
+//! Backtracking then restores the domains together with the guesses.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Puzzle;
+
+/// A set of small integer values from 0 to 63, stored as bits.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Domain(pub u64);
+
+impl Domain {
+    /// The largest number of values in a domain.
+    pub const CAPACITY: u32 = 64;
+
+    /// Creates an empty domain.
+    pub fn empty() -> Domain {Domain(0)}
+
+    /// Creates a domain containing a single value.
+    ///
+    /// Panics if the value is not less than `Domain::CAPACITY`.
+    pub fn singleton(val: u32) -> Domain {
+        let mut res = Domain::empty();
+        res.insert(val);
+        res
+    }
+
+    /// Creates a domain containing the values from `start` to `end` (exclusive).
+    pub fn range(start: u32, end: u32) -> Domain {
+        let mut res = Domain::empty();
+        for v in start..end.min(Domain::CAPACITY) {res.insert(v)}
+        res
+    }
+
+    /// Returns `true` if the domain contains a value.
+    pub fn contains(self, val: u32) -> bool {
+        val < Domain::CAPACITY && self.0 & (1 << val) != 0
+    }
+
+    /// Inserts a value.
+    ///
+    /// Panics if the value is not less than `Domain::CAPACITY`.
+    pub fn insert(&mut self, val: u32) {
+        assert!(val < Domain::CAPACITY, "Domain value {} is out of range 0..{}", val, Domain::CAPACITY);
+        self.0 |= 1 << val
+    }
+
+    /// Removes a value.
+    ///
+    /// Values that are out of range are never contained, so removing them does nothing.
+    pub fn remove(&mut self, val: u32) {
+        if val < Domain::CAPACITY {self.0 &= !(1 << val)}
+    }
+
+    /// Returns the number of values.
+    pub fn len(self) -> usize {self.0.count_ones() as usize}
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(self) -> bool {self.0 == 0}
+
+    /// Returns the value if there is exactly one value.
+    pub fn value(self) -> Option<u32> {
+        if self.0.is_power_of_two() {Some(self.0.trailing_zeros())} else {None}
+    }
+
+    /// Returns the smallest value.
+    pub fn min(self) -> Option<u32> {
+        if self.is_empty() {None} else {Some(self.0.trailing_zeros())}
+    }
+
+    /// Returns the largest value.
+    pub fn max(self) -> Option<u32> {
+        if self.is_empty() {None} else {Some(63 - self.0.leading_zeros())}
+    }
+
+    /// Returns the values in both domains.
+    pub fn intersect(self, other: Domain) -> Domain {Domain(self.0 & other.0)}
+
+    /// Returns the values in either domain.
+    pub fn union(self, other: Domain) -> Domain {Domain(self.0 | other.0)}
+
+    /// Returns the values of this domain that are not in the other.
+    pub fn difference(self, other: Domain) -> Domain {Domain(self.0 & !other.0)}
+
+    /// Iterates over values from smallest to largest.
+    pub fn iter(self) -> DomainIter {DomainIter(self.0)}
+}
+
+impl fmt::Debug for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl IntoIterator for Domain {
+    type Item = u32;
+    type IntoIter = DomainIter;
+    fn into_iter(self) -> DomainIter {self.iter()}
+}
+
+/// Iterates over the values of a domain.
+pub struct DomainIter(u64);
+
+impl Iterator for DomainIter {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {return None};
+        let v = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(v)
+    }
+}
+
+impl DoubleEndedIterator for DomainIter {
+    fn next_back(&mut self) -> Option<u32> {
+        if self.0 == 0 {return None};
+        let v = 63 - self.0.leading_zeros();
+        self.0 &= !(1 << v);
+        Some(v)
+    }
+}
+
+/// Gives propagators access to domains, while keeping track of changes.
+pub struct DomainStore<'a> {
+    domains: &'a mut [Domain],
+    changed: Vec<usize>,
+}
+
+impl<'a> DomainStore<'a> {
+    /// Creates a new domain store.
+    pub fn new(domains: &'a mut [Domain]) -> DomainStore<'a> {
+        DomainStore {domains, changed: vec![]}
+    }
+
+    /// Gets the domain of a variable.
+    pub fn get(&self, var: usize) -> Domain {self.domains[var]}
+
+    /// Returns the number of variables.
+    pub fn len(&self) -> usize {self.domains.len()}
+
+    /// Returns `true` if there are no variables.
+    pub fn is_empty(&self) -> bool {self.domains.is_empty()}
+
+    /// Keeps only values of a variable that are in the domain.
+    ///
+    /// Returns `false` if the variable has no values left.
+    pub fn restrict(&mut self, var: usize, dom: Domain) -> bool {
+        let old = self.domains[var];
+        let new = old.intersect(dom);
+        if new != old {
+            self.domains[var] = new;
+            if !self.changed.contains(&var) {self.changed.push(var)};
+        }
+        !new.is_empty()
+    }
+
+    /// Removes a value from a variable.
+    ///
+    /// Returns `false` if the variable has no values left.
+    pub fn remove(&mut self, var: usize, val: u32) -> bool {
+        let mut dom = Domain(!0);
+        dom.remove(val);
+        self.restrict(var, dom)
+    }
+
+    /// Returns the variables changed so far.
+    pub fn changed(&self) -> &[usize] {&self.changed}
+}
+
+/// Implemented by propagators.
+pub trait Propagator {
+    /// Returns the variables that wake up the propagator when changed.
+    fn watches(&self) -> Vec<usize>;
+    /// Removes values that can not be part of a solution.
+    ///
+    /// Returns `false` if there is no solution.
+    fn propagate(&self, store: &mut DomainStore) -> bool;
+    /// The name of the propagator, used for debugging.
+    fn name(&self) -> &str {"propagator"}
+}
+
+/// Stores propagators and runs them to a fixpoint.
+#[derive(Default)]
+pub struct Propagation {
+    propagators: Vec<Box<dyn Propagator + Send + Sync>>,
+    watches: Vec<Vec<usize>>,
+    watchers: Vec<Vec<usize>>,
+    /// The number of times each propagator removed values or found a conflict.
+    fired: Vec<AtomicU64>,
+}
+
+impl Propagation {
+    /// Creates a new empty propagation engine.
+    pub fn new() -> Propagation {
+        Propagation::default()
+    }
+
+    /// Adds a propagator.
+    pub fn add<P>(&mut self, propagator: P) where P: Propagator + Send + Sync + 'static {
+        let id = self.propagators.len();
+        let watches = propagator.watches();
+        for &var in &watches {
+            if var >= self.watchers.len() {self.watchers.resize(var + 1, vec![])};
+            if !self.watchers[var].contains(&id) {self.watchers[var].push(id)};
+        }
+        self.watches.push(watches);
+        self.fired.push(AtomicU64::new(0));
+        self.propagators.push(Box::new(propagator));
+    }
+
+    /// Adds a propagator.
+    pub fn with<P>(mut self, propagator: P) -> Self where P: Propagator + Send + Sync + 'static {
+        self.add(propagator);
+        self
+    }
+
+    /// Returns the number of propagators.
+    pub fn len(&self) -> usize {self.propagators.len()}
+
+    /// Returns `true` if there are no propagators.
+    pub fn is_empty(&self) -> bool {self.propagators.is_empty()}
+
+    /// Returns the propagators watching a variable.
+    pub fn watchers(&self, var: usize) -> &[usize] {
+        self.watchers.get(var).map(|w| &w[..]).unwrap_or(&[])
+    }
+
+    /// Returns the other variables watched by the propagators watching a variable.
+    pub fn neighbors(&self, var: usize) -> Vec<usize> {
+        let mut res = vec![];
+        for &id in self.watchers(var) {
+            for &other in &self.watches[id] {
+                if other != var && !res.contains(&other) {res.push(other)};
+            }
+        }
+        res
+    }
+
+    /// Runs propagators watching the changed variables until no more values are removed.
+    ///
+    /// Returns `false` if a propagator finds that there is no solution.
+    pub fn fixpoint(&self, domains: &mut [Domain], changed: &[usize]) -> bool {
+        let mut queue = VecDeque::new();
+        let mut queued = vec![false; self.propagators.len()];
+        for &var in changed {
+            for &id in self.watchers(var) {
+                if !queued[id] {
+                    queued[id] = true;
+                    queue.push_back(id);
+                }
+            }
+        }
+        while let Some(id) = queue.pop_front() {
+            queued[id] = false;
+            let mut store = DomainStore::new(domains);
+            if !self.propagators[id].propagate(&mut store) {
+                self.fired[id].fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+            if !store.changed.is_empty() {
+                self.fired[id].fetch_add(1, Ordering::Relaxed);
+            }
+            for &var in &store.changed {
+                for &other in self.watchers(var) {
+                    if other != id && !queued[other] {
+                        queued[other] = true;
+                        queue.push_back(other);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the number of times propagators removed values or found a conflict,
+    /// added up for propagators with the same name.
+    ///
+    /// The counts are shared by all clones of a `Csp`, since they share the propagation.
+    pub fn fired(&self) -> Vec<(String, u64)> {
+        let mut res: Vec<(String, u64)> = vec![];
+        for (propagator, fired) in self.propagators.iter().zip(&self.fired) {
+            let n = fired.load(Ordering::Relaxed);
+            match res.iter_mut().find(|(name, _)| name == propagator.name()) {
+                Some(entry) => entry.1 += n,
+                None => res.push((propagator.name().into(), n)),
+            }
+        }
+        res
+    }
+
+    /// Sets the number of times propagators fired to zero.
+    pub fn reset_fired(&self) {
+        for fired in &self.fired {
+            fired.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Runs all propagators until no more values are removed.
+    ///
+    /// Returns `false` if a propagator finds that there is no solution.
+    pub fn fixpoint_all(&self, domains: &mut [Domain]) -> bool {
+        let all: Vec<usize> = (0..self.watchers.len()).collect();
+        self.fixpoint(domains, &all)
+    }
+}
+
+/// Requires all variables to have different values.
+pub struct AllDifferent {
+    /// The variables.
+    pub vars: Vec<usize>,
+}
+
+impl AllDifferent {
+    /// Creates a new all-different constraint.
+    pub fn new(vars: Vec<usize>) -> AllDifferent {
+        AllDifferent {vars}
+    }
+}
+
+impl Propagator for AllDifferent {
+    fn watches(&self) -> Vec<usize> {self.vars.clone()}
+
+    fn propagate(&self, store: &mut DomainStore) -> bool {
+        loop {
+            let mut fixed = Domain::empty();
+            loop {
+                let mut found_any = false;
+                for &var in &self.vars {
+                    if let Some(v) = store.get(var).value() {
+                        if fixed.contains(v) {continue};
+                        fixed.insert(v);
+                        found_any = true;
+                        for &other in &self.vars {
+                            if other != var && !store.remove(other, v) {return false}
+                        }
+                    }
+                }
+                if !found_any {break}
+            }
+
+            // There must be enough values for all variables.
+            let all = self.vars.iter().fold(Domain::empty(), |acc, &var| acc.union(store.get(var)));
+            if all.len() < self.vars.len() {return false};
+            if all.len() > self.vars.len() {return true};
+
+            // All values are used, so a value that fits only one variable must be used by it.
+            let mut found_any = false;
+            for v in all.difference(fixed).iter() {
+                let mut vars = self.vars.iter().filter(|&&var| store.get(var).contains(v));
+                if let (Some(&var), None) = (vars.next(), vars.next()) {
+                    if !store.restrict(var, Domain::singleton(v)) {return false};
+                    found_any = true;
+                }
+            }
+            if !found_any {return true}
+        }
+    }
+
+    fn name(&self) -> &str {"all different"}
+}
+
+/// Requires a relation to hold between two variables.
+///
+/// Values without support in the other domain are removed, as in AC-3.
+pub struct Binary<F> {
+    /// The first variable.
+    pub a: usize,
+    /// The second variable.
+    pub b: usize,
+    /// The relation between values of the first and second variable.
+    pub relation: F,
+}
+
+impl<F> Binary<F> where F: Fn(u32, u32) -> bool {
+    /// Creates a new binary constraint.
+    pub fn new(a: usize, b: usize, relation: F) -> Binary<F> {
+        Binary {a, b, relation}
+    }
+}
+
+impl<F> Propagator for Binary<F> where F: Fn(u32, u32) -> bool {
+    fn watches(&self) -> Vec<usize> {vec![self.a, self.b]}
+
+    fn propagate(&self, store: &mut DomainStore) -> bool {
+        let (da, db) = (store.get(self.a), store.get(self.b));
+        let mut sa = Domain::empty();
+        let mut sb = Domain::empty();
+        for x in da.iter() {
+            for y in db.iter() {
+                if (self.relation)(x, y) {
+                    sa.insert(x);
+                    sb.insert(y);
+                }
+            }
+        }
+        store.restrict(self.a, sa) && store.restrict(self.b, sb)
+    }
+
+    fn name(&self) -> &str {"binary"}
+}
+
+/// A constraint problem with variables and propagators.
+///
+/// The positions are variables and the values are domains.
+/// A guess sets the domain of a variable to a single value,
+/// and `solve_simple` runs the propagators from the changed variables.
+///
+/// Use `Csp::select_min` and `Csp::values` as the closures of the solver.
+#[derive(Clone)]
+pub struct Csp {
+    /// The domains of variables.
+    pub domains: Vec<Domain>,
+    /// The propagators.
+    pub propagation: Arc<Propagation>,
+    changed: Vec<usize>,
+}
+
+impl Csp {
+    /// Creates a new constraint problem.
+    ///
+    /// All propagators run on the first call to `solve_simple`.
+    pub fn new(domains: Vec<Domain>, propagation: Propagation) -> Csp {
+        let changed = (0..domains.len()).collect();
+        Csp {domains, propagation: Arc::new(propagation), changed}
+    }
+
+    /// Gets the value of a variable, if it has only one value.
+    pub fn value(&self, var: usize) -> Option<u32> {
+        self.domains[var].value()
+    }
+
+    /// Finds the first variable with more than one value.
+    ///
+    /// Returns `None` if some variable has no values left.
+    pub fn select_first(&self) -> Option<usize> {
+        if self.domains.iter().any(|d| d.is_empty()) {return None};
+        self.domains.iter().position(|d| d.len() > 1)
+    }
+
+    /// Finds the variable with fewest values, but more than one.
+    ///
+    /// Returns `None` if some variable has no values left.
+    pub fn select_min(&self) -> Option<usize> {
+        let mut min: Option<(usize, usize)> = None;
+        for (i, d) in self.domains.iter().enumerate() {
+            let n = d.len();
+            if n == 0 {return None};
+            if n > 1 && min.is_none_or(|(_, m)| m > n) {
+                min = Some((i, n));
+            }
+        }
+        min.map(|(i, _)| i)
+    }
+
+    /// Returns the possible guesses for a variable.
+    ///
+    /// The smallest value is tried first.
+    pub fn values(&self, var: usize) -> Vec<Domain> {
+        self.domains[var].iter().rev().map(Domain::singleton).collect()
+    }
+}
+
+impl Puzzle for Csp {
+    type Pos = usize;
+    type Val = Domain;
+
+    fn solve_simple<F: FnMut(&mut Self, usize, Domain)>(&mut self, mut f: F) {
+        if self.changed.is_empty() {return};
+        let mut domains = self.domains.clone();
+        if self.propagation.fixpoint(&mut domains, &self.changed) {
+            for (var, dom) in domains.into_iter().enumerate() {
+                if dom != self.domains[var] {
+                    f(self, var, dom);
+                }
+            }
+        } else {
+            // Propagators can report a conflict without emptying a domain,
+            // so an empty domain is set to make the solver backtrack.
+            let var = self.changed[0];
+            f(self, var, Domain::empty());
+        }
+        self.changed.clear();
+    }
+
+    fn set(&mut self, pos: usize, val: Domain) {
+        self.domains[pos] = val;
+        self.changed.push(pos);
+    }
+
+    fn get(&self, pos: usize) -> Domain {
+        self.domains[pos]
+    }
+
+    fn print(&self) {
+        println!("{:?}", self.domains);
+    }
+
+    fn is_solved(&self) -> bool {
+        self.domains.iter().all(|d| d.len() == 1)
+    }
+
+    fn remove(&mut self, other: &Csp) {
+        for (var, dom) in other.domains.iter().enumerate() {
+            if dom.len() == 1 {
+                self.domains[var] = Domain::empty();
+            }
+        }
+    }
+
+    fn write_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(w, "{:?}", self.domains)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BackTrackSolver, SolveSettings};
+
+    #[test]
+    fn domain_bounds() {
+        let mut d = Domain::singleton(63);
+        assert_eq!(d.value(), Some(63));
+        assert_eq!(d.max(), Some(63));
+        assert!(!d.contains(64));
+        d.insert(0);
+        d.remove(64);
+        assert_eq!(d.iter().collect::<Vec<_>>(), vec![0, 63]);
+        d.remove(63);
+        assert_eq!(d.value(), Some(0));
+        assert_eq!(Domain::range(60, 100).iter().collect::<Vec<_>>(), vec![60, 61, 62, 63]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Domain value 64 is out of range 0..64")]
+    fn singleton_out_of_range() {
+        Domain::singleton(64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Domain value 64 is out of range 0..64")]
+    fn insert_out_of_range() {
+        Domain::empty().insert(64);
+    }
+
+    /// Requires the sum of two variables, but only reports a conflict when both are assigned.
+    struct Sum {
+        vars: [usize; 2],
+        sum: u32,
+    }
+
+    impl Propagator for Sum {
+        fn watches(&self) -> Vec<usize> {self.vars.to_vec()}
+
+        fn propagate(&self, store: &mut DomainStore) -> bool {
+            match (store.get(self.vars[0]).value(), store.get(self.vars[1]).value()) {
+                (Some(a), Some(b)) => a + b == self.sum,
+                _ => true,
+            }
+        }
+    }
+
+    fn solve(sum: u32) -> Option<Csp> {
+        let propagation = Propagation::new().with(Sum {vars: [0, 1], sum});
+        let csp = Csp::new(vec![Domain::range(0, 2); 2], propagation);
+        let solver = BackTrackSolver::new(csp, SolveSettings::new());
+        solver.solve(Csp::select_min, |s: &Csp, var| s.values(var)).map(|solution| solution.puzzle)
+    }
+
+    #[test]
+    fn conflict_without_empty_domain() {
+        assert!(solve(3).is_none());
+    }
+
+    #[test]
+    fn conflict_backtracks_to_solution() {
+        let csp = solve(1).unwrap();
+        assert_eq!(csp.value(0).unwrap() + csp.value(1).unwrap(), 1);
+    }
+}
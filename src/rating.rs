@@ -0,0 +1,253 @@
+//! Difficulty rating.
+
+use crate::progress::ProgressClock;
+use crate::{drive_search, Puzzle, Search, SearchHooks, SolveSettings, Stop, Strategy};
+
+/// The difficulty tier of a puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tier {
+    /// Solved mostly by simple steps.
+    Easy,
+    /// Needs a few guesses.
+    Medium,
+    /// Needs many guesses or deep search.
+    Hard,
+    /// Needs extensive search.
+    Expert,
+}
+
+/// Stores measurements of how a solver solved a puzzle.
+///
+/// This can be used as hooks to measure any search.
+#[derive(Clone, Debug, Default)]
+pub struct Measurements {
+    /// The number of values set by guesses, including guesses that were undone.
+    pub guesses: u64,
+    /// The number of guesses undone.
+    pub backtracks: u64,
+    /// The maximum number of guesses on the stack.
+    pub max_depth: usize,
+    /// The number of values set by simple steps, including steps that were undone.
+    pub simple: u64,
+    /// The number of times each rule fired, if the puzzle reports rules.
+    pub rules: Vec<(String, u64)>,
+    /// The strategy that was measured.
+    pub strategy: usize,
+}
+
+impl Measurements {
+    /// Creates new empty measurements.
+    pub fn new() -> Measurements {
+        Measurements::default()
+    }
+
+    /// Returns the share of values set by simple steps, from 0 to 1.
+    pub fn simple_share(&self) -> f64 {
+        let total = self.simple + self.guesses;
+        if total == 0 {1.0} else {self.simple as f64 / total as f64}
+    }
+}
+
+impl<T> SearchHooks<T> for Measurements where T: Puzzle {
+    fn guess(&mut self, _state: &T, _pos: T::Pos, _val: T::Val, depth: usize) {
+        self.guesses += 1;
+        self.max_depth = self.max_depth.max(depth);
+    }
+
+    fn simple(&mut self, _state: &T, _pos: T::Pos, _val: T::Val) {
+        self.simple += 1;
+    }
+
+    fn backtrack(&mut self, _state: &T, _depth: usize) {
+        self.backtracks += 1;
+    }
+}
+
+/// Stores the difficulty rating of a puzzle.
+#[derive(Clone, Debug)]
+pub struct Rating {
+    /// The difficulty score, where higher is harder.
+    pub score: f64,
+    /// The difficulty tier of the score.
+    pub tier: Tier,
+    /// The measurements of the strategy with lowest score.
+    pub measurements: Measurements,
+}
+
+/// Rates the difficulty of puzzles by how much work the solver needs.
+///
+/// Each strategy solves the puzzle while the rater measures
+/// the number of guesses, the maximum depth and the share of values set by simple steps.
+/// Puzzles can also report which rules fired, for example `Propagation::fired`.
+/// The measurements are combined into a score:
+///
+/// ```text
+/// score = guess_weight * ln(1 + guesses)
+///       + depth_weight * max_depth
+///       + simple_weight * (1 - simple_share)
+///       + sum of rule_weight * ln(1 + fired)
+/// ```
+///
+/// The easiest strategy decides the rating, since a human solver
+/// would pick the technique that works best for the puzzle.
+/// The weights and tier thresholds are calibrated for Sudoku by default.
+pub struct Rater {
+    /// Stores solve settings.
+    pub settings: SolveSettings,
+    /// The weight of the logarithm of guesses.
+    pub guess_weight: f64,
+    /// The weight of the maximum depth.
+    pub depth_weight: f64,
+    /// The weight of the share of values not set by simple steps.
+    pub simple_weight: f64,
+    /// The weights of the logarithm of the number of times rules fired, by rule name.
+    ///
+    /// Rules without a weight do not count.
+    pub rule_weights: Vec<(String, f64)>,
+    /// The lowest scores of the tiers `Medium`, `Hard` and `Expert`.
+    pub tiers: [f64; 3],
+}
+
+impl Rater {
+    /// Creates a new rater.
+    pub fn new(settings: SolveSettings) -> Rater {
+        Rater {
+            settings,
+            guess_weight: 1.0,
+            depth_weight: 0.5,
+            simple_weight: 2.0,
+            rule_weights: vec![],
+            tiers: [1.0, 5.0, 10.0],
+        }
+    }
+
+    /// Sets the weight of the logarithm of guesses.
+    pub fn set_guess_weight(&mut self, val: f64) {
+        self.guess_weight = val;
+    }
+
+    /// The weight of the logarithm of guesses.
+    pub fn guess_weight(mut self, val: f64) -> Self {
+        self.set_guess_weight(val);
+        self
+    }
+
+    /// Sets the weight of the maximum depth.
+    pub fn set_depth_weight(&mut self, val: f64) {
+        self.depth_weight = val;
+    }
+
+    /// The weight of the maximum depth.
+    pub fn depth_weight(mut self, val: f64) -> Self {
+        self.set_depth_weight(val);
+        self
+    }
+
+    /// Sets the weight of the share of values not set by simple steps.
+    pub fn set_simple_weight(&mut self, val: f64) {
+        self.simple_weight = val;
+    }
+
+    /// The weight of the share of values not set by simple steps.
+    pub fn simple_weight(mut self, val: f64) -> Self {
+        self.set_simple_weight(val);
+        self
+    }
+
+    /// Sets the weight of a rule.
+    pub fn set_rule_weight(&mut self, name: &str, val: f64) {
+        match self.rule_weights.iter_mut().find(|(n, _)| n == name) {
+            Some(entry) => entry.1 = val,
+            None => self.rule_weights.push((name.into(), val)),
+        }
+    }
+
+    /// The weight of a rule.
+    pub fn rule_weight(mut self, name: &str, val: f64) -> Self {
+        self.set_rule_weight(name, val);
+        self
+    }
+
+    /// Sets the lowest scores of the tiers `Medium`, `Hard` and `Expert`.
+    pub fn set_tiers(&mut self, val: [f64; 3]) {
+        self.tiers = val;
+    }
+
+    /// The lowest scores of the tiers `Medium`, `Hard` and `Expert`.
+    pub fn tiers(mut self, val: [f64; 3]) -> Self {
+        self.set_tiers(val);
+        self
+    }
+
+    /// Computes the score of measurements.
+    pub fn score(&self, measurements: &Measurements) -> f64 {
+        let mut score = self.guess_weight * (measurements.guesses as f64).ln_1p() +
+            self.depth_weight * measurements.max_depth as f64 +
+            self.simple_weight * (1.0 - measurements.simple_share());
+        for (name, fired) in &measurements.rules {
+            if let Some((_, weight)) = self.rule_weights.iter().find(|(n, _)| n == name) {
+                score += weight * (*fired as f64).ln_1p();
+            }
+        }
+        score
+    }
+
+    /// Returns the tier of a score.
+    pub fn tier(&self, score: f64) -> Tier {
+        if score >= self.tiers[2] {Tier::Expert}
+        else if score >= self.tiers[1] {Tier::Hard}
+        else if score >= self.tiers[0] {Tier::Medium}
+        else {Tier::Easy}
+    }
+
+    /// Rates a puzzle using strategies like `MultiBackTrackSolver`.
+    ///
+    /// Returns `None` if no strategy solves the puzzle within `SolveSettings::max_iterations`.
+    pub fn rate<T>(
+        &self,
+        puzzle: &T,
+        strategies: &[Strategy<T>]
+    ) -> Option<Rating>
+        where T: Puzzle
+    {
+        self.rate_with_rules(puzzle, strategies, |_| vec![])
+    }
+
+    /// Rates a puzzle, using a closure that returns the number of times each rule fired.
+    ///
+    /// The closure is called before and after each strategy,
+    /// such that counts shared between states only include the rules fired by the strategy.
+    pub fn rate_with_rules<T, R>(
+        &self,
+        puzzle: &T,
+        strategies: &[Strategy<T>],
+        mut rules: R
+    ) -> Option<Rating>
+        where T: Puzzle, R: FnMut(&T) -> Vec<(String, u64)>
+    {
+        let mut best: Option<Rating> = None;
+        for (i, &strategy) in strategies.iter().enumerate() {
+            let before = rules(puzzle);
+            let mut search = Search::new(puzzle.clone());
+            let mut policy = strategy;
+            let mut measurements = Measurements {strategy: i, ..Measurements::new()};
+            let mut iterations: u64 = 0;
+            let mut clock = ProgressClock::new();
+            let stop = drive_search(&self.settings, &mut iterations, &mut clock,
+                &mut search, &mut policy, &mut measurements);
+            let solved = matches!(stop, Stop::Solved);
+            let after = rules(&search.state);
+            measurements.rules = after.into_iter().map(|(name, n)| {
+                let m = before.iter().find(|(b, _)| *b == name).map(|&(_, m)| m).unwrap_or(0);
+                (name, n.saturating_sub(m))
+            }).collect();
+            if !solved {continue};
+
+            let score = self.score(&measurements);
+            if best.as_ref().is_none_or(|b| score < b.score) {
+                best = Some(Rating {score, tier: self.tier(score), measurements});
+            }
+        }
+        best
+    }
+}
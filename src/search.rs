@@ -0,0 +1,344 @@
+//! Search engine shared by the backtracking solvers.
+
+use crate::{Puzzle, SearchHooks, SolveSettings, Trail};
+
+/// Implemented by policies that decide where to search next.
+///
+/// A policy picks the position to set a value next, and the order of values to try.
+/// A pair of closures `(f, g)` is a policy, where `f` picks a position
+/// and `g` returns the possible values at a position.
+pub trait Policy<T: Puzzle> {
+    /// Picks the next position to set a value, or `None` if no position can be set.
+    fn select(&mut self, state: &T) -> Option<T::Pos>;
+    /// Returns the possible values at a position.
+    ///
+    /// The last value in the list has highest priority, because it is tried first.
+    fn order(&mut self, state: &T, pos: T::Pos) -> Vec<T::Val>;
+    /// Called after a value is set by a choice.
+    fn assigned(&mut self, _state: &T, _pos: T::Pos, _val: T::Val) {}
+}
+
+impl<T, F, G> Policy<T> for (F, G)
+    where T: Puzzle,
+          F: FnMut(&T) -> Option<T::Pos>,
+          G: FnMut(&T, T::Pos) -> Vec<T::Val>
+{
+    fn select(&mut self, state: &T) -> Option<T::Pos> {(self.0)(state)}
+    fn order(&mut self, state: &T, pos: T::Pos) -> Vec<T::Val> {(self.1)(state, pos)}
+}
+
+impl<T, P> Policy<T> for &mut P where T: Puzzle, P: Policy<T> {
+    fn select(&mut self, state: &T) -> Option<T::Pos> {(**self).select(state)}
+    fn order(&mut self, state: &T, pos: T::Pos) -> Vec<T::Val> {(**self).order(state, pos)}
+    fn assigned(&mut self, state: &T, pos: T::Pos, val: T::Val) {(**self).assigned(state, pos, val)}
+}
+
+/// The result of a step of the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// A value was set, and the search can continue.
+    Continue,
+    /// The state is solved.
+    Solved,
+    /// All choices were tried without finding a solution.
+    Exhausted,
+    /// All choices were tried, but `SolveSettings::max_depth` cut the search short.
+    DepthLimited,
+    /// All choices were tried, but `Search::discrepancy_budget` cut the search short.
+    DiscrepancyLimited,
+}
+
+/// Stores the state of a depth first search with backtracking.
+///
+/// Each call to `step` sets one value by a choice, backtracking first if needed.
+/// The solvers call `step` in a loop, which makes it possible to interleave
+/// several searches, or to write custom search drivers.
+/// A custom driver can call `checkpoint` before trying something,
+/// and `restore` to undo it without cloning the puzzle.
+pub struct Search<T> where T: Puzzle {
+    /// Stores the state.
+    pub state: T,
+    /// Stores the previous values of positions, to undo choices.
+    pub trail: Trail<T>,
+    /// Stores the choices for the state.
+    pub choice: Vec<(T::Pos, Vec<T::Val>)>,
+    /// The maximum number of discrepancies on the path to a state, if any.
+    ///
+    /// A discrepancy is a choice that does not take the first value tried.
+    /// When the budget is used up, only the first value is tried at new choices.
+    pub discrepancy_budget: Option<u32>,
+    /// Whether the depth limit cut the search short.
+    depth_limited: bool,
+    /// Whether the discrepancy budget cut the search short.
+    discrepancy_limited: bool,
+    /// Choices below this depth have been cut short by the depth limit or discrepancy budget,
+    /// or lead to a solution rejected by the hooks,
+    /// so running out of values does not prove that there is no solution.
+    cut_depth: usize,
+    /// The number of values at each choice when it was made.
+    branches: Vec<usize>,
+    /// Whether the end of the search is printed to standard output instead of standard error,
+    /// as `MultiBackTrackSolver` does.
+    pub(crate) stdout: bool,
+}
+
+impl<T> Search<T> where T: Puzzle {
+    /// Creates a new search starting from a state.
+    pub fn new(state: T) -> Search<T> {
+        Search {
+            state,
+            trail: Trail::new(),
+            choice: vec![],
+            discrepancy_budget: None,
+            depth_limited: false,
+            discrepancy_limited: false,
+            cut_depth: 0,
+            branches: vec![],
+            stdout: false,
+        }
+    }
+
+    /// Returns a mark that can be passed to `restore`.
+    pub fn checkpoint(&self) -> usize {
+        self.trail.checkpoint()
+    }
+
+    /// Undoes changes until the state is the same as when the mark was created.
+    ///
+    /// Choices made after the mark are removed from the choice stack.
+    pub fn restore(&mut self, mark: usize) {
+        self.trail.restore(&mut self.state, mark);
+        self.choice.truncate(self.trail.choices());
+        self.branches.truncate(self.choice.len());
+        self.cut_depth = self.cut_depth.min(self.choice.len());
+    }
+
+    /// Goes back to the starting state.
+    pub fn reset(&mut self) {
+        self.restore(0);
+        self.depth_limited = false;
+        self.discrepancy_limited = false;
+        self.cut_depth = 0;
+    }
+
+    /// Estimates the fraction of the search tree explored, from 0 to 1.
+    ///
+    /// Each choice splits the remaining search tree into equal parts, one for each value.
+    /// The values tried before the current one at each choice count as explored.
+    /// This is Knuth's estimator, which assumes that the subtrees are of similar size.
+    pub fn explored(&self) -> f64 {
+        explored(&self.branches, &self.choice)
+    }
+
+    /// Returns the number of choices where the current value is not the first value tried.
+    pub fn discrepancies(&self) -> u32 {
+        self.branches.iter().zip(&self.choice)
+            .filter(|&(&n, (_, possible))| possible.len() + 1 < n).count() as u32
+    }
+
+    /// Writes a debug message about the end of the search.
+    fn trace_end(&self, settings: &SolveSettings, msg: std::fmt::Arguments) {
+        if self.stdout {settings.trace_stdout(msg)} else {settings.trace(msg)}
+    }
+
+    /// Solves simple steps, then sets a value by a choice, backtracking if needed.
+    ///
+    /// The iteration number is only used for printing.
+    pub fn step<P, H>(
+        &mut self,
+        policy: &mut P,
+        hooks: &mut H,
+        settings: &SolveSettings,
+        iterations: u64
+    ) -> Step
+        where P: Policy<T>, H: SearchHooks<T>
+    {
+        if settings.solve_simple {
+            let trail = &mut self.trail;
+            self.state.solve_simple(|state, pos, val| {
+                trail.set_simple(state, pos, val);
+                hooks.simple(state, pos, val);
+            });
+        }
+        if settings.debug {
+            settings.trace_state(&self.state);
+        }
+        // A solved state that is rejected by the hooks is backtracked from like a dead end.
+        let solved = self.state.is_solved();
+        if solved && hooks.accept(&self.state) {
+            hooks.solved(&self.state, self.choice.len());
+            if settings.debug {
+                self.trace_end(settings, format_args!("Solved! Iterations: {}", iterations));
+            }
+            return Step::Solved;
+        }
+
+        let cut = settings.max_depth.is_some_and(|max| self.choice.len() >= max);
+        let empty = if cut || solved || hooks.is_dead(&self.state) {None} else {policy.select(&self.state)};
+        let mut possible = match empty {
+            None => vec![],
+            Some(x) => {
+                let state = &self.state;
+                let mut possible = policy.order(state, x);
+                possible.retain(|&v| !hooks.prune(state, x, v));
+                possible
+            }
+        };
+        let budget_cut = possible.len() > 1 &&
+            self.discrepancy_budget.is_some_and(|max| self.discrepancies() >= max);
+        if budget_cut {
+            // Only the first value is tried, since other values would exceed the budget.
+            possible.drain(..possible.len() - 1);
+            self.discrepancy_limited = true;
+        }
+        if let Some(v) = possible.pop() {
+            let empty = empty.unwrap();
+            // Put in the first guess.
+            self.trail.set(&mut self.state, empty, v);
+            policy.assigned(&self.state, empty, v);
+            self.branches.push(possible.len() + 1);
+            self.choice.push((empty, possible));
+            if budget_cut {
+                self.cut_depth = self.choice.len();
+            }
+            hooks.guess(&self.state, empty, v, self.choice.len());
+            if settings.debug {
+                settings.trace(format_args!("Guess {:?}, {:?} depth ch: {} prev: {} it: {}",
+                    empty, v, self.choice.len(), self.trail.len(), iterations));
+            }
+            return Step::Continue;
+        }
+
+        if cut {
+            if settings.debug {
+                settings.trace(format_args!("Max depth reached"));
+            }
+            self.depth_limited = true;
+            self.cut_depth = self.choice.len();
+        } else if solved {
+            // A rejected solution is not a dead end, so hooks must not learn from it.
+            self.cut_depth = self.choice.len();
+        } else {
+            hooks.dead_end(&self.state, &self.choice);
+        }
+        while let Some((pos, mut possible)) = self.choice.pop() {
+            let branches = self.branches.pop().unwrap_or(possible.len() + 1);
+            self.trail.undo_choice(&mut self.state);
+            hooks.backtrack(&self.state, self.choice.len());
+            // Skip values learned to fail since the choice was made.
+            while let Some(&v) = possible.last() {
+                if !hooks.prune(&self.state, pos, v) {break}
+                possible.pop();
+            }
+            if let Some(new_val) = possible.pop() {
+                // Try next choice.
+                self.trail.set(&mut self.state, pos, new_val);
+                policy.assigned(&self.state, pos, new_val);
+                self.branches.push(branches);
+                self.choice.push((pos, possible));
+                hooks.guess(&self.state, pos, new_val, self.choice.len());
+                if settings.debug {
+                    settings.trace(format_args!("Try   {:?}, {:?} depth ch: {} prev: {} (failed at {:?}) it: {}",
+                        pos, new_val, self.choice.len(), self.trail.len(), empty, iterations));
+                }
+                return Step::Continue;
+            } else if self.choice.len() >= self.cut_depth {
+                // All values at this position failed.
+                hooks.dead_end(&self.state, &self.choice);
+            } else {
+                self.cut_depth = self.choice.len();
+            }
+        }
+        if settings.debug {
+            // No more possible choices.
+            self.trace_end(settings, format_args!("No more possible choices"));
+        }
+        if self.discrepancy_limited {Step::DiscrepancyLimited}
+        else if self.depth_limited {Step::DepthLimited}
+        else {Step::Exhausted}
+    }
+}
+
+/// Estimates the fraction of the search tree explored, from the number of values at each choice.
+fn explored<P, V>(branches: &[usize], choice: &[(P, Vec<V>)]) -> f64 {
+    let mut part = 1.0;
+    let mut explored = 0.0;
+    for (&n, (_, possible)) in branches.iter().zip(choice) {
+        let n = n as f64;
+        explored += part * (n - possible.len() as f64 - 1.0) / n;
+        part /= n;
+    }
+    explored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{RejectFirst, Row};
+    use crate::{BackTrackSolver, NogoodStore, TranspositionTable};
+
+    fn solve<H>(hooks: &mut H) -> Option<Vec<u8>> where H: crate::SearchHooks<Row> {
+        let solver = BackTrackSolver::new(Row::new(2, 2, 2), SolveSettings::new());
+        solver.solve_with_hooks(Row::first_empty, Row::values, hooks).map(|s| s.puzzle.cells)
+    }
+
+    #[test]
+    fn rejected_solution_is_not_a_nogood() {
+        let mut store = NogoodStore::new(100);
+        assert_eq!(solve(&mut (RejectFirst::default(), &mut store)), None);
+        assert!(store.is_empty());
+        assert_eq!(solve(&mut store), Some(vec![1, 1]));
+    }
+
+    #[test]
+    fn rejected_solution_is_not_a_dead_state() {
+        let mut table = TranspositionTable::by_state(100);
+        assert_eq!(solve(&mut (RejectFirst::default(), &mut table)), None);
+        assert!(table.is_empty());
+        assert_eq!(solve(&mut table), Some(vec![1, 1]));
+    }
+
+    #[test]
+    fn rejected_solutions_are_all_found() {
+        let (all, outcome) = Row::new(3, 3, 6).solutions(SolveSettings::new(), &mut ());
+        assert!(matches!(outcome, crate::SolveOutcome::Exhausted));
+        assert_eq!(all.len(), 7);
+        assert!(all.iter().all(|cells| cells.iter().map(|&v| v as u32).sum::<u32>() == 6));
+    }
+
+    #[test]
+    fn checkpoint_and_restore() {
+        let settings = SolveSettings::new();
+        let mut policy = (Row::first_empty, Row::values);
+        let mut search = Search::new(Row::new(3, 3, 6));
+        assert_eq!(search.step(&mut policy, &mut (), &settings, 1), Step::Continue);
+        let first = search.state.clone();
+        let mark = search.checkpoint();
+
+        assert_eq!(search.step(&mut policy, &mut (), &settings, 2), Step::Continue);
+        search.trail.set_simple(&mut search.state, 2, 3);
+        assert_eq!(search.state.cells, vec![1, 1, 3]);
+        assert_eq!(search.choice.len(), 2);
+
+        search.restore(mark);
+        assert_eq!(search.state, first);
+        assert_eq!(search.choice.len(), 1);
+        // The search continues from the restored state.
+        let mut iterations = 2;
+        loop {
+            iterations += 1;
+            match search.step(&mut policy, &mut (), &settings, iterations) {
+                Step::Continue => {}
+                step => {
+                    assert_eq!(step, Step::Solved);
+                    break;
+                }
+            }
+        }
+        assert_eq!(search.state.cells, vec![1, 2, 3]);
+
+        search.restore(0);
+        assert_eq!(search.state, Row::new(3, 3, 6));
+        assert!(search.choice.is_empty() && search.trail.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+//! Heuristics for picking the next position to set a value.
+//!
+//! The functions in this module can be passed directly as the first closure of a solver,
+//! e.g. `solver.solve(select::min_remaining_values, Domains::candidates)`.
+//! Heuristics that learn during search return a closure or have a `select` method.
+//!
+//! All heuristics return `None` when there are no unassigned positions.
+//! Heuristics that look at domains also return `None` when some unassigned position
+//! has no candidates left, which makes the solver backtrack immediately.
+
+use std::hash::Hash;
+
+use fnv::FnvHashMap;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{BitDomains, Domains, Puzzle};
+use crate::propagate::Csp;
+
+/// Implemented by puzzles that can list their unassigned positions.
+pub trait Variables: Puzzle {
+    /// Returns the positions without a value, in preferred order.
+    fn unassigned(&self) -> Vec<Self::Pos>;
+    /// Returns the positions that share a constraint with a position.
+    ///
+    /// This is used by heuristics that prefer positions involved in many constraints.
+    fn neighbors(&self, _pos: Self::Pos) -> Vec<Self::Pos> {vec![]}
+}
+
+impl Variables for Csp {
+    fn unassigned(&self) -> Vec<usize> {
+        (0..self.domains.len()).filter(|&i| self.domains[i].len() != 1).collect()
+    }
+
+    fn neighbors(&self, pos: usize) -> Vec<usize> {
+        self.propagation.neighbors(pos)
+    }
+}
+
+impl Variables for BitDomains {
+    fn unassigned(&self) -> Vec<usize> {
+        (0..self.len()).filter(|&i| Puzzle::get(self, i).is_none()).collect()
+    }
+
+    fn neighbors(&self, pos: usize) -> Vec<usize> {
+        self.peers(pos).to_vec()
+    }
+}
+
+/// Picks the first unassigned position.
+pub fn first_unassigned<T: Variables>(state: &T) -> Option<T::Pos> {
+    state.unassigned().first().cloned()
+}
+
+/// Picks the unassigned position with fewest candidates (minimum remaining values).
+///
+/// Ties are broken by the order of unassigned positions.
+pub fn min_remaining_values<T: Variables + Domains>(state: &T) -> Option<T::Pos> {
+    let mut min: Option<(T::Pos, usize)> = None;
+    for pos in state.unassigned() {
+        let n = state.domain_size(pos);
+        if n == 0 {return None};
+        if min.is_none_or(|(_, m)| m > n) {
+            min = Some((pos, n));
+        }
+    }
+    min.map(|(pos, _)| pos)
+}
+
+/// Picks the unassigned position with most unassigned neighbors (maximum degree).
+///
+/// Ties are broken by the order of unassigned positions.
+pub fn max_degree<T>(state: &T) -> Option<T::Pos>
+    where T: Variables, T::Pos: PartialEq
+{
+    let unassigned = state.unassigned();
+    let mut max: Option<(T::Pos, usize)> = None;
+    for &pos in &unassigned {
+        let n = state.neighbors(pos).iter().filter(|p| unassigned.contains(p)).count();
+        if max.is_none_or(|(_, m)| m < n) {
+            max = Some((pos, n));
+        }
+    }
+    max.map(|(pos, _)| pos)
+}
+
+/// Picks the unassigned position with fewest candidates,
+/// breaking ties by most unassigned neighbors.
+pub fn min_remaining_values_degree<T>(state: &T) -> Option<T::Pos>
+    where T: Variables + Domains, T::Pos: PartialEq
+{
+    let unassigned = state.unassigned();
+    let mut min: Option<(T::Pos, usize, usize)> = None;
+    for &pos in &unassigned {
+        let n = state.domain_size(pos);
+        if n == 0 {return None};
+        if min.is_some_and(|(_, m, _)| m < n) {continue};
+        let degree = state.neighbors(pos).iter().filter(|p| unassigned.contains(p)).count();
+        if min.is_none_or(|(_, m, d)| m > n || d < degree) {
+            min = Some((pos, n, degree));
+        }
+    }
+    min.map(|(pos, _, _)| pos)
+}
+
+/// Returns a closure that picks a random unassigned position.
+pub fn random<T: Variables>(seed: u64) -> impl FnMut(&T) -> Option<T::Pos> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    move |state: &T| {
+        let unassigned = state.unassigned();
+        if unassigned.is_empty() {None}
+        else {Some(unassigned[rng.random_range(0..unassigned.len())])}
+    }
+}
+
+/// Returns a closure that picks the unassigned position with lowest key,
+/// breaking ties at random, using a seed for the random number generator.
+///
+/// For example, `random_ties(seed, |s, p| s.domain_size(p))` is minimum remaining values
+/// with random tie breaking.
+pub fn random_ties<T, K>(seed: u64, mut key: K) -> impl FnMut(&T) -> Option<T::Pos>
+    where T: Variables, K: FnMut(&T, T::Pos) -> usize
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    move |state: &T| {
+        let mut ties: Vec<T::Pos> = vec![];
+        let mut min: Option<usize> = None;
+        for pos in state.unassigned() {
+            let k = key(state, pos);
+            if min.is_none_or(|m| m > k) {
+                min = Some(k);
+                ties.clear();
+            }
+            if min == Some(k) {ties.push(pos)};
+        }
+        if ties.is_empty() {None}
+        else {Some(ties[rng.random_range(0..ties.len())])}
+    }
+}
+
+/// Picks the position with smallest ratio of candidates to failure weight (dom/wdeg).
+///
+/// Every time a position runs out of candidates, the weight of the position
+/// and its neighbors increases. Positions that are often involved in failures
+/// are therefore tried earlier, which helps the solver to fail early.
+///
+/// The weights are kept between calls, so the same heuristic can be reused across restarts.
+pub struct DomWdeg<P> {
+    /// Stores the failure weight of positions, starting at 1.
+    pub weights: FnvHashMap<P, f64>,
+}
+
+impl<P> Default for DomWdeg<P> {
+    fn default() -> DomWdeg<P> {
+        DomWdeg {weights: FnvHashMap::default()}
+    }
+}
+
+impl<P> DomWdeg<P> where P: Copy + Hash + Eq {
+    /// Creates a new heuristic without failures.
+    pub fn new() -> DomWdeg<P> {
+        DomWdeg::default()
+    }
+
+    /// Gets the failure weight of a position.
+    pub fn weight(&self, pos: P) -> f64 {
+        self.weights.get(&pos).cloned().unwrap_or(1.0)
+    }
+
+    /// Increases the failure weight of a position and its neighbors.
+    pub fn fail<T>(&mut self, state: &T, pos: P)
+        where T: Variables<Pos = P>
+    {
+        *self.weights.entry(pos).or_insert(1.0) += 1.0;
+        for p in state.neighbors(pos) {
+            *self.weights.entry(p).or_insert(1.0) += 1.0;
+        }
+    }
+
+    /// Picks the next position.
+    pub fn select<T>(&mut self, state: &T) -> Option<P>
+        where T: Variables<Pos = P> + Domains
+    {
+        let mut min: Option<(P, f64)> = None;
+        for pos in state.unassigned() {
+            let n = state.domain_size(pos);
+            if n == 0 {
+                self.fail(state, pos);
+                return None;
+            }
+            let score = n as f64 / self.weight(pos);
+            if min.is_none_or(|(_, m)| m > score) {
+                min = Some((pos, score));
+            }
+        }
+        min.map(|(pos, _)| pos)
+    }
+}
+
+/// Returns a closure that picks positions using a new `DomWdeg` heuristic.
+pub fn dom_wdeg<T>() -> impl FnMut(&T) -> Option<T::Pos>
+    where T: Variables + Domains, T::Pos: Hash + Eq
+{
+    let mut heuristic = DomWdeg::new();
+    move |state: &T| heuristic.select(state)
+}
@@ -0,0 +1,138 @@
+//! Puzzles used by tests.
+
+use crate::{BackTrackSolver, Puzzle, SearchHooks, SolveOutcome, SolveSettings};
+
+/// A row of cells with values from 1 to `max`, solved when the values add up to `sum`.
+///
+/// Values are only offered if the remaining cells can still be set,
+/// so partial states run into dead ends when the sum is too small.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Row {
+    pub cells: Vec<u8>,
+    pub max: u8,
+    pub sum: u32,
+}
+
+impl Row {
+    pub fn new(len: usize, max: u8, sum: u32) -> Row {
+        Row {cells: vec![0; len], max, sum}
+    }
+
+    pub fn total(&self) -> u32 {
+        self.cells.iter().map(|&v| v as u32).sum()
+    }
+
+    pub fn first_empty(&self) -> Option<usize> {
+        self.cells.iter().position(|&v| v == 0)
+    }
+
+    /// Returns the values that fit at a position, smallest last so it is tried first.
+    pub fn values(&self, _pos: usize) -> Vec<u8> {
+        let empty = self.cells.iter().filter(|&&v| v == 0).count() as u32;
+        (1..=self.max).rev().filter(|&v| self.total() + v as u32 + empty - 1 <= self.sum).collect()
+    }
+
+    /// Finds all solutions by rejecting every solved state.
+    pub fn solutions<H>(&self, settings: SolveSettings, hooks: &mut H) -> (Vec<Vec<u8>>, SolveOutcome<Row>)
+        where H: SearchHooks<Row>
+    {
+        let mut collect = Collect(vec![]);
+        let solver = BackTrackSolver::new(self.clone(), settings);
+        let outcome = solver.solve_outcome(Row::first_empty, Row::values, &mut (&mut collect, hooks));
+        (collect.0, outcome)
+    }
+}
+
+impl Puzzle for Row {
+    type Pos = usize;
+    type Val = u8;
+
+    fn set(&mut self, pos: usize, val: u8) {self.cells[pos] = val}
+    fn get(&self, pos: usize) -> u8 {self.cells[pos]}
+    fn print(&self) {println!("{:?}", self.cells)}
+    fn is_solved(&self) -> bool {
+        self.first_empty().is_none() && self.total() == self.sum
+    }
+    fn remove(&mut self, other: &Row) {
+        for (a, &b) in self.cells.iter_mut().zip(&other.cells) {
+            if b != 0 {*a = 0}
+        }
+    }
+}
+
+/// Collects solved states and rejects them, such that the search continues.
+pub(crate) struct Collect(pub Vec<Vec<u8>>);
+
+impl SearchHooks<Row> for Collect {
+    fn accept(&mut self, state: &Row) -> bool {
+        self.0.push(state.cells.clone());
+        false
+    }
+}
+
+/// Rejects the first solved state.
+#[derive(Default)]
+pub(crate) struct RejectFirst(pub bool);
+
+impl<T: Puzzle> SearchHooks<T> for RejectFirst {
+    fn accept(&mut self, _state: &T) -> bool {
+        let rejected = self.0;
+        self.0 = true;
+        rejected
+    }
+}
+
+/// Places one queen per row on an `n` by `n` board, such that no two queens attack each other.
+///
+/// Rows are filled from the top, and the value is the column plus one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Queens {
+    pub rows: Vec<u8>,
+}
+
+impl Queens {
+    pub fn new(n: usize) -> Queens {
+        Queens {rows: vec![0; n]}
+    }
+
+    pub fn first_empty(&self) -> Option<usize> {
+        self.rows.iter().position(|&v| v == 0)
+    }
+
+    fn attacks(&self, row: usize, col: u8) -> bool {
+        self.rows.iter().enumerate().any(|(r, &c)| {
+            c != 0 && r != row && (c == col || r.abs_diff(row) == c.abs_diff(col) as usize)
+        })
+    }
+
+    /// Returns the columns that are not attacked in a row, leftmost last.
+    pub fn values(&self, row: usize) -> Vec<u8> {
+        (1..=self.rows.len() as u8).rev().filter(|&c| !self.attacks(row, c)).collect()
+    }
+
+    /// Counts the cells that are not attacked in empty rows.
+    pub fn free(&self) -> f64 {
+        (0..self.rows.len()).filter(|&r| self.rows[r] == 0)
+            .map(|r| self.values(r).len()).sum::<usize>() as f64
+    }
+
+    /// Returns `true` if every row has a queen and no queens attack each other.
+    pub fn is_valid(&self) -> bool {
+        self.rows.iter().enumerate().all(|(r, &c)| c != 0 && !self.attacks(r, c))
+    }
+}
+
+impl Puzzle for Queens {
+    type Pos = usize;
+    type Val = u8;
+
+    fn set(&mut self, pos: usize, val: u8) {self.rows[pos] = val}
+    fn get(&self, pos: usize) -> u8 {self.rows[pos]}
+    fn print(&self) {println!("{:?}", self.rows)}
+    fn is_solved(&self) -> bool {self.is_valid()}
+    fn remove(&mut self, other: &Queens) {
+        for (a, &b) in self.rows.iter_mut().zip(&other.rows) {
+            if b != 0 {*a = 0}
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Recorded search traces.
+
+use std::fmt;
+
+use crate::{Puzzle, SearchHooks, Trail};
+
+/// A step of the search stored in a trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent<P, V> {
+    /// A value was set by a guess.
+    Guess(P, V),
+    /// A value was set by a simple step.
+    Simple(P, V),
+    /// The last guess was undone, leaving this number of guesses.
+    Backtrack(usize),
+    /// The state was solved.
+    Solved,
+}
+
+/// Records the steps of a search, such that it can be analyzed offline or replayed.
+///
+/// Use it with `BackTrackSolver::solve_with_hooks`.
+///
+/// The text format has one event per line, with fields separated by tabs:
+///
+/// ```text
+/// g   <pos>   <val>
+/// s   <pos>   <val>
+/// b   <depth>
+/// solved
+/// ```
+///
+/// Positions and values are written with `Debug`,
+/// and read back with closures passed to `parse`.
+///
+/// Two traces of the same puzzle are equal when the searches made the same steps,
+/// which can be used to check that an optimization did not change the search.
+#[derive(Clone, Debug)]
+pub struct Trace<P, V> {
+    /// Stores the events in the order they happened.
+    pub events: Vec<TraceEvent<P, V>>,
+}
+
+impl<P, V> Default for Trace<P, V> {
+    fn default() -> Trace<P, V> {
+        Trace::new()
+    }
+}
+
+impl<P, V> Trace<P, V> {
+    /// Creates a new empty trace.
+    pub fn new() -> Trace<P, V> {
+        Trace {events: vec![]}
+    }
+
+    /// Returns the number of events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if there are no events.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Removes all events.
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// Reads a trace from text, using a closure to parse positions and a closure to parse values.
+    ///
+    /// Returns the index of the first line that can not be read, if any.
+    pub fn parse<F, G>(text: &str, mut f: F, mut g: G) -> Result<Trace<P, V>, usize>
+        where F: FnMut(&str) -> Option<P>,
+              G: FnMut(&str) -> Option<V>
+    {
+        let mut events = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let mut fields = line.split('\t');
+            let event = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                (Some("g"), Some(pos), Some(val), None) => f(pos).zip(g(val))
+                    .map(|(pos, val)| TraceEvent::Guess(pos, val)),
+                (Some("s"), Some(pos), Some(val), None) => f(pos).zip(g(val))
+                    .map(|(pos, val)| TraceEvent::Simple(pos, val)),
+                (Some("b"), Some(depth), None, None) => depth.parse().ok().map(TraceEvent::Backtrack),
+                (Some("solved"), None, None, None) => Some(TraceEvent::Solved),
+                _ => None,
+            };
+            match event {
+                Some(event) => events.push(event),
+                None => return Err(i),
+            }
+        }
+        Ok(Trace {events})
+    }
+
+    /// Drives a puzzle through the trace, checking each step.
+    ///
+    /// The puzzle should be in the state the search started from.
+    /// Each guessed value must be among the possible values returned by `g`
+    /// for the replayed state, which is the closure for possible values used by the search.
+    /// A backtrack must undo a guess, and the state must be solved where the trace says so.
+    /// The last closure is called after each event, and can do further checks by returning `false`.
+    ///
+    /// Returns the index of the first event that fails a check, if any.
+    pub fn replay<T, G, F>(&self, state: &mut T, mut g: G, mut f: F) -> Result<(), usize>
+        where T: Puzzle<Pos = P, Val = V>,
+              G: FnMut(&T, P) -> Vec<V>,
+              F: FnMut(&T, &TraceEvent<P, V>) -> bool,
+              P: Copy, V: Copy + PartialEq
+    {
+        let mut trail: Trail<T> = Trail::new();
+        let mut depth = 0;
+        for (i, event) in self.events.iter().enumerate() {
+            match *event {
+                TraceEvent::Guess(pos, val) => {
+                    if !g(state, pos).contains(&val) {return Err(i)};
+                    trail.set(state, pos, val);
+                    depth += 1;
+                }
+                TraceEvent::Simple(pos, val) => trail.set_simple(state, pos, val),
+                TraceEvent::Backtrack(d) => {
+                    if d >= depth {return Err(i)};
+                    while depth > d {
+                        trail.undo_choice(state);
+                        depth -= 1;
+                    }
+                }
+                TraceEvent::Solved => {
+                    if !state.is_solved() {return Err(i)};
+                }
+            }
+            if !f(state, event) {return Err(i)};
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the first event where two traces differ, if any.
+    ///
+    /// When one trace is a prefix of the other, this is the length of the shorter trace.
+    pub fn first_difference(&self, other: &Trace<P, V>) -> Option<usize>
+        where P: PartialEq, V: PartialEq
+    {
+        let n = self.events.len().min(other.events.len());
+        (0..n).find(|&i| self.events[i] != other.events[i])
+            .or(if self.events.len() == other.events.len() {None} else {Some(n)})
+    }
+}
+
+impl<P, V> Trace<P, V> where P: fmt::Debug, V: fmt::Debug {
+    /// Writes the trace in text format.
+    pub fn write_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        for event in &self.events {
+            match *event {
+                TraceEvent::Guess(ref pos, ref val) => writeln!(w, "g\t{:?}\t{:?}", pos, val)?,
+                TraceEvent::Simple(ref pos, ref val) => writeln!(w, "s\t{:?}\t{:?}", pos, val)?,
+                TraceEvent::Backtrack(depth) => writeln!(w, "b\t{}", depth)?,
+                TraceEvent::Solved => writeln!(w, "solved")?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the trace in text format.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        self.write_to(&mut text).unwrap();
+        text
+    }
+}
+
+impl<T> SearchHooks<T> for Trace<T::Pos, T::Val>
+    where T: Puzzle
+{
+    fn guess(&mut self, _state: &T, pos: T::Pos, val: T::Val, _depth: usize) {
+        self.events.push(TraceEvent::Guess(pos, val));
+    }
+
+    fn simple(&mut self, _state: &T, pos: T::Pos, val: T::Val) {
+        self.events.push(TraceEvent::Simple(pos, val));
+    }
+
+    fn backtrack(&mut self, _state: &T, depth: usize) {
+        self.events.push(TraceEvent::Backtrack(depth));
+    }
+
+    fn solved(&mut self, _state: &T, _depth: usize) {
+        self.events.push(TraceEvent::Solved);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+    use crate::{BackTrackSolver, SolveSettings};
+
+    /// Records a search that backtracks before finding the solution `[1, 2, 2]`.
+    fn record() -> Trace<usize, u8> {
+        let mut trace = Trace::new();
+        let solver = BackTrackSolver::new(Row::new(3, 2, 5), SolveSettings::new());
+        let solution = solver.solve_with_hooks(Row::first_empty, Row::values, &mut trace).unwrap();
+        assert_eq!(solution.puzzle.cells, vec![1, 2, 2]);
+        trace
+    }
+
+    fn parse(text: &str) -> Trace<usize, u8> {
+        Trace::parse(text, |s| s.parse().ok(), |s| s.parse().ok()).unwrap()
+    }
+
+    fn replay(trace: &Trace<usize, u8>) -> Result<Row, usize> {
+        let mut state = Row::new(3, 2, 5);
+        trace.replay(&mut state, Row::values, |_, _| true).map(|()| state)
+    }
+
+    #[test]
+    fn round_trip() {
+        let trace = record();
+        assert!(trace.events.contains(&TraceEvent::Backtrack(1)));
+        let text = trace.to_text();
+        let parsed = parse(&text);
+        assert_eq!(parsed.first_difference(&trace), None);
+        assert_eq!(parsed.to_text(), text);
+        assert_eq!(replay(&parsed).unwrap().cells, vec![1, 2, 2]);
+    }
+
+    #[test]
+    fn tampered_guess() {
+        let trace = record();
+        assert_eq!(trace.events[0], TraceEvent::Guess(0, 1));
+
+        // A value that is never possible.
+        let mut tampered = trace.clone();
+        tampered.events[0] = TraceEvent::Guess(0, 3);
+        assert_eq!(replay(&tampered), Err(0));
+
+        // A value that is possible at the start, but not in the replayed state.
+        let tampered = Trace {events: vec![
+            TraceEvent::Guess(0, 2),
+            TraceEvent::Guess(1, 2),
+            TraceEvent::Guess(2, 2),
+        ]};
+        assert!(Row::new(3, 2, 5).values(2).contains(&2));
+        assert_eq!(replay(&tampered), Err(2));
+    }
+
+    #[test]
+    fn tampered_text() {
+        let text = format!("solved\n{}", record().to_text());
+        assert_eq!(replay(&parse(&text)), Err(0));
+        let text = record().to_text().replacen("g\t1\t1\n", "b\t5\n", 1);
+        assert_eq!(replay(&parse(&text)), Err(1));
+        assert_eq!(Trace::<usize, u8>::parse("g\t0", |s| s.parse().ok(), |s| s.parse().ok()).unwrap_err(), 0);
+    }
+}
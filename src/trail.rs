@@ -0,0 +1,98 @@
+//! Undo trail for puzzles.
+
+use crate::Puzzle;
+
+/// Stores the previous values of positions, such that changes to a puzzle can be undone.
+///
+/// This is used by the solvers to backtrack without cloning the puzzle.
+/// A custom search can call `checkpoint` before trying something,
+/// and `restore` to go back to the state at the checkpoint.
+///
+/// Values are either set by a choice, or by a simple step that follows from earlier choices.
+/// `undo_choice` undoes the last choice together with the simple steps that followed it.
+pub struct Trail<T> where T: Puzzle {
+    /// Stores the previous values of a position before setting a value.
+    /// If the flag is true, the value was inserted due to a simple choice.
+    pub prevs: Vec<(T::Pos, T::Val, bool)>,
+}
+
+impl<T> Default for Trail<T> where T: Puzzle {
+    fn default() -> Trail<T> {
+        Trail {prevs: vec![]}
+    }
+}
+
+impl<T> Clone for Trail<T> where T: Puzzle {
+    fn clone(&self) -> Trail<T> {
+        Trail {prevs: self.prevs.clone()}
+    }
+}
+
+impl<T> Trail<T> where T: Puzzle {
+    /// Creates a new empty trail.
+    pub fn new() -> Trail<T> {
+        Trail::default()
+    }
+
+    /// Returns the number of changes stored in the trail.
+    pub fn len(&self) -> usize {
+        self.prevs.len()
+    }
+
+    /// Returns `true` if there are no changes to undo.
+    pub fn is_empty(&self) -> bool {
+        self.prevs.is_empty()
+    }
+
+    /// Returns the number of choices stored in the trail.
+    pub fn choices(&self) -> usize {
+        self.prevs.iter().filter(|p| !p.2).count()
+    }
+
+    /// Returns a mark that can be passed to `restore`.
+    pub fn checkpoint(&self) -> usize {
+        self.prevs.len()
+    }
+
+    /// Undoes changes until the state is the same as when the mark was created.
+    pub fn restore(&mut self, state: &mut T, mark: usize) {
+        while self.prevs.len() > mark {
+            let (pos, val, _) = self.prevs.pop().unwrap();
+            state.set(pos, val);
+        }
+    }
+
+    /// Sets a value by a choice.
+    pub fn set(&mut self, state: &mut T, pos: T::Pos, val: T::Val) {
+        self.prevs.push((pos, state.get(pos), false));
+        state.set(pos, val);
+    }
+
+    /// Sets a value by a simple step.
+    pub fn set_simple(&mut self, state: &mut T, pos: T::Pos, val: T::Val) {
+        self.prevs.push((pos, state.get(pos), true));
+        state.set(pos, val);
+    }
+
+    /// Solves simple steps of the puzzle, storing the changes in the trail.
+    pub fn solve_simple(&mut self, state: &mut T) {
+        let prevs = &mut self.prevs;
+        state.solve_simple(|state, pos, val| {
+            prevs.push((pos, state.get(pos), true));
+            state.set(pos, val);
+        });
+    }
+
+    /// Undoes the last choice and the simple steps that followed it.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo_choice(&mut self, state: &mut T) -> bool {
+        let mut undo = false;
+        while let Some((pos, val, simple)) = self.prevs.pop() {
+            state.set(pos, val);
+            undo = true;
+            if !simple {break}
+        }
+        undo
+    }
+}
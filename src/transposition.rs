@@ -0,0 +1,211 @@
+//! Transposition table.
+
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use fnv::FnvHashSet;
+
+use crate::{Puzzle, SearchHooks, SearchStats};
+
+/// Remembers states that are proven to have no solution.
+///
+/// Some puzzles reach the same state through different orders of guesses.
+/// When a state is stored as a dead end, the solver backtracks immediately
+/// the next time it comes up, instead of searching it again.
+///
+/// States are stored by a key computed from the state.
+/// The key can be the state itself, see `TranspositionTable::by_state`,
+/// or a fingerprint of the state that identifies it uniquely.
+/// Keys that are equal for different states might prune states with solutions.
+///
+/// The table is bounded by a capacity, removing the oldest key first.
+/// Use it with `BackTrackSolver::solve_with_hooks`.
+pub struct TranspositionTable<K, F> {
+    dead: FnvHashSet<K>,
+    order: VecDeque<K>,
+    key: F,
+    /// The maximum number of states to store.
+    pub capacity: usize,
+    /// The number of states found in the table.
+    pub hits: u64,
+    /// The number of states not found in the table.
+    pub misses: u64,
+}
+
+impl<K, F> TranspositionTable<K, F>
+    where K: Clone + Hash + Eq
+{
+    /// Creates a new transposition table using a closure to compute keys.
+    pub fn new(capacity: usize, key: F) -> TranspositionTable<K, F> {
+        TranspositionTable {
+            dead: FnvHashSet::default(),
+            order: VecDeque::new(),
+            key,
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the number of stored states.
+    pub fn len(&self) -> usize {
+        self.dead.len()
+    }
+
+    /// Returns `true` if there are no stored states.
+    pub fn is_empty(&self) -> bool {
+        self.dead.is_empty()
+    }
+
+    /// Removes all stored states, but keeps hit and miss counts.
+    pub fn clear(&mut self) {
+        self.dead.clear();
+        self.order.clear();
+    }
+
+    /// Returns the ratio of hits to lookups.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {0.0} else {self.hits as f64 / lookups as f64}
+    }
+
+    /// Returns `true` if the key is stored as a dead end.
+    pub fn contains(&self, key: &K) -> bool {
+        self.dead.contains(key)
+    }
+
+    /// Stores a key as a dead end.
+    pub fn insert(&mut self, key: K) {
+        if self.capacity == 0 || self.dead.contains(&key) {return};
+
+        while self.dead.len() >= self.capacity {
+            match self.order.pop_front() {
+                None => break,
+                Some(old) => {self.dead.remove(&old);}
+            }
+        }
+        self.dead.insert(key.clone());
+        self.order.push_back(key);
+    }
+}
+
+impl<T> TranspositionTable<T, fn(&T) -> T>
+    where T: Clone + Hash + Eq
+{
+    /// Creates a new transposition table that stores whole states.
+    pub fn by_state(capacity: usize) -> TranspositionTable<T, fn(&T) -> T> {
+        TranspositionTable::new(capacity, T::clone)
+    }
+}
+
+impl<T, K, F> SearchHooks<T> for TranspositionTable<K, F>
+    where T: Puzzle, K: Clone + Hash + Eq, F: FnMut(&T) -> K
+{
+    fn is_dead(&mut self, state: &T) -> bool {
+        if self.dead.is_empty() {
+            self.misses += 1;
+            return false;
+        }
+        if self.dead.contains(&(self.key)(state)) {
+            self.hits += 1;
+            true
+        } else {
+            self.misses += 1;
+            false
+        }
+    }
+
+    fn dead_end(&mut self, state: &T, _choice: &[(T::Pos, Vec<T::Val>)]) {
+        let key = (self.key)(state);
+        self.insert(key);
+    }
+
+    fn stats(&self, stats: &mut SearchStats) {
+        stats.hits += self.hits;
+        stats.misses += self.misses;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::Row;
+    use crate::{BackTrackSolver, SolveSettings};
+
+    fn is_dead<K, F>(table: &mut TranspositionTable<K, F>, row: &Row) -> bool
+        where K: Clone + Hash + Eq, F: FnMut(&Row) -> K
+    {
+        SearchHooks::<Row>::is_dead(table, row)
+    }
+
+    #[test]
+    fn evicts_oldest_first() {
+        let mut table = TranspositionTable::new(2, |&x: &u32| x);
+        table.insert(1);
+        table.insert(2);
+        table.insert(3);
+        assert!(!table.contains(&1));
+        assert!(table.contains(&2) && table.contains(&3));
+        // Inserting a stored key does not make it newer.
+        table.insert(2);
+        table.insert(4);
+        assert!(!table.contains(&2));
+        assert!(table.contains(&3) && table.contains(&4));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn zero_capacity_stores_nothing() {
+        let mut table = TranspositionTable::new(0, |&x: &u32| x);
+        table.insert(1);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn hit_rate() {
+        let mut table = TranspositionTable::by_state(10);
+        assert_eq!(table.hit_rate(), 0.0);
+        let dead = Row {cells: vec![1, 0], max: 2, sum: 4};
+        let other = Row {cells: vec![2, 0], max: 2, sum: 4};
+        table.insert(dead.clone());
+        assert!(is_dead(&mut table, &dead));
+        assert!(!is_dead(&mut table, &other));
+        assert!(!is_dead(&mut table, &other));
+        assert_eq!((table.hits, table.misses), (1, 2));
+        assert_eq!(table.hit_rate(), 1.0 / 3.0);
+        table.clear();
+        assert!(!is_dead(&mut table, &dead));
+        assert_eq!(table.hit_rate(), 0.25);
+    }
+
+    #[test]
+    fn transposed_states_are_pruned() {
+        // Rows with the same number of cells set and the same total have the same
+        // remaining search, so the key tells them apart only as much as needed.
+        let key = |row: &Row| (row.cells.iter().filter(|&&v| v != 0).count(), row.total());
+        let all = Row::new(4, 3, 8).solutions(SolveSettings::new(), &mut ()).0;
+        let mut table = TranspositionTable::new(1000, key);
+        assert_eq!(Row::new(4, 3, 8).solutions(SolveSettings::new(), &mut table).0, all);
+        assert!(table.hits > 0);
+    }
+
+    #[test]
+    fn key_collisions_prune_solutions() {
+        // Every state has the same key, so the first dead end prunes everything after it.
+        let all = Row::new(4, 3, 8).solutions(SolveSettings::new(), &mut ()).0;
+        let mut table = TranspositionTable::new(1000, |_: &Row| ());
+        let found = Row::new(4, 3, 8).solutions(SolveSettings::new(), &mut table).0;
+        assert!(found.len() < all.len());
+        assert!(found.iter().all(|row| all.contains(row)));
+        assert_eq!(table.len(), 1);
+    }
+
+    #[test]
+    fn stats_in_solution() {
+        let mut table = TranspositionTable::by_state(1000);
+        let solver = BackTrackSolver::new(Row::new(4, 3, 11), SolveSettings::new());
+        let solution = solver.solve_with_hooks(Row::first_empty, Row::values, &mut table).unwrap();
+        assert_eq!(solution.stats, SearchStats {hits: table.hits, misses: table.misses});
+        assert!(solution.stats.misses > 0);
+    }
+}
@@ -0,0 +1,192 @@
+//! Search tree recording.
+
+use std::fmt::{self, Debug};
+
+use crate::{Puzzle, SearchHooks};
+
+/// Tells how a node in the recorded search tree ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeMark {
+    /// The node was not proven to fail, either because the search
+    /// was cut short or because it is on the path to a solution.
+    Open,
+    /// The state of the node has no solution.
+    Dead,
+    /// The state of the node is solved.
+    Solved,
+}
+
+/// Stores a node in the recorded search tree.
+#[derive(Clone, Debug)]
+pub struct TreeNode<P, V> {
+    /// The guess that lead to the node, or `None` for the starting state.
+    pub guess: Option<(P, V)>,
+    /// The index of the parent node. The starting state is its own parent.
+    pub parent: usize,
+    /// The number of guesses that lead to the node.
+    pub depth: usize,
+    /// How the node ended.
+    pub mark: NodeMark,
+}
+
+/// Records the search tree explored by a solver.
+///
+/// Every guess becomes a node, with an edge from the node of the guess before.
+/// Nodes are marked when they are proven to have no solution or are solved.
+/// The tree can be exported to Graphviz DOT and rendered with `dot -Tsvg`.
+///
+/// Large searches create many nodes, so the recorder can be limited
+/// to a maximum depth or a maximum number of nodes.
+/// Guesses beyond the limits are counted but not recorded.
+///
+/// Use it with `BackTrackSolver::solve_with_hooks`.
+/// When used with iterative deepening, each iteration adds a new subtree to the starting state.
+pub struct TreeRecorder<P, V> {
+    /// Stores the nodes, where the first node is the starting state.
+    pub nodes: Vec<TreeNode<P, V>>,
+    /// The maximum depth of recorded nodes, if any.
+    pub max_depth: Option<usize>,
+    /// The maximum number of recorded nodes, if any.
+    pub max_nodes: Option<usize>,
+    /// The number of guesses that were not recorded due to the limits.
+    pub skipped: u64,
+    /// The recorded node at each depth of the current guesses.
+    path: Vec<Option<usize>>,
+}
+
+impl<P, V> Default for TreeRecorder<P, V> {
+    fn default() -> TreeRecorder<P, V> {
+        TreeRecorder::new()
+    }
+}
+
+impl<P, V> TreeRecorder<P, V> {
+    /// Creates a new recorder without limits.
+    pub fn new() -> TreeRecorder<P, V> {
+        TreeRecorder {
+            nodes: vec![TreeNode {guess: None, parent: 0, depth: 0, mark: NodeMark::Open}],
+            max_depth: None,
+            max_nodes: None,
+            skipped: 0,
+            path: vec![Some(0)],
+        }
+    }
+
+    /// Sets the maximum depth of recorded nodes.
+    pub fn set_max_depth(&mut self, val: usize) {
+        self.max_depth = Some(val);
+    }
+
+    /// The maximum depth of recorded nodes.
+    pub fn max_depth(mut self, val: usize) -> Self {
+        self.set_max_depth(val);
+        self
+    }
+
+    /// Sets the maximum number of recorded nodes.
+    pub fn set_max_nodes(&mut self, val: usize) {
+        self.max_nodes = Some(val);
+    }
+
+    /// The maximum number of recorded nodes.
+    pub fn max_nodes(mut self, val: usize) -> Self {
+        self.set_max_nodes(val);
+        self
+    }
+
+    /// Returns the number of recorded nodes, including the starting state.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if no guesses are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() <= 1
+    }
+
+    fn mark(&mut self, depth: usize, mark: NodeMark) {
+        if let Some(&Some(node)) = self.path.get(depth) {
+            self.nodes[node].mark = mark;
+        }
+    }
+}
+
+impl<P, V> TreeRecorder<P, V> where P: Debug, V: Debug {
+    /// Writes the tree in Graphviz DOT format.
+    ///
+    /// Dead nodes are filled red and solved nodes are filled green.
+    pub fn write_dot(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(w, "digraph search {{")?;
+        writeln!(w, "    node [shape=box, style=filled, fillcolor=white];")?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = match node.guess {
+                None => "start".into(),
+                Some((ref pos, ref val)) => escape(&format!("{:?}\n{:?}", pos, val)),
+            };
+            let color = match node.mark {
+                NodeMark::Open => "",
+                NodeMark::Dead => ", fillcolor=\"#ffb0b0\"",
+                NodeMark::Solved => ", fillcolor=\"#b0ffb0\"",
+            };
+            writeln!(w, "    n{} [label=\"{}\"{}];", i, label, color)?;
+            if i != 0 {
+                writeln!(w, "    n{} -> n{};", node.parent, i)?;
+            }
+        }
+        if self.skipped > 0 {
+            writeln!(w, "    // {} guesses not recorded", self.skipped)?;
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Returns the tree in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        let mut text = String::new();
+        self.write_dot(&mut text).unwrap();
+        text
+    }
+}
+
+/// Escapes text for use in a quoted DOT label.
+fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            _ => res.push(c),
+        }
+    }
+    res
+}
+
+impl<T> SearchHooks<T> for TreeRecorder<T::Pos, T::Val>
+    where T: Puzzle
+{
+    fn dead_end(&mut self, _state: &T, choice: &[(T::Pos, Vec<T::Val>)]) {
+        self.mark(choice.len(), NodeMark::Dead);
+    }
+
+    fn guess(&mut self, _state: &T, pos: T::Pos, val: T::Val, depth: usize) {
+        self.path.truncate(depth);
+        let parent = self.path.last().copied().flatten();
+        let full = self.max_nodes.is_some_and(|max| self.nodes.len() >= max);
+        let deep = self.max_depth.is_some_and(|max| depth > max);
+        let node = match parent {
+            Some(parent) if !full && !deep => {
+                self.nodes.push(TreeNode {guess: Some((pos, val)), parent, depth, mark: NodeMark::Open});
+                Some(self.nodes.len() - 1)
+            }
+            _ => {
+                self.skipped += 1;
+                None
+            }
+        };
+        self.path.push(node);
+    }
+
+    fn solved(&mut self, _state: &T, depth: usize) {
+        self.mark(depth, NodeMark::Solved);
+    }
+}
@@ -0,0 +1,237 @@
+//! Terminal viewer for live solving.
+
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{Grid2D, SearchHooks};
+
+/// The shortest time between redraws when there is no delay.
+const FRAME: Duration = Duration::from_millis(33);
+
+/// Shows the search live in a terminal, redrawing the puzzle in place using ANSI escape codes.
+///
+/// The cell just guessed is highlighted green, and the cell just undone is highlighted red.
+/// Below the grid, the viewer shows the depth, guesses per second and the number of backtracks.
+///
+/// The terminal sends input line by line, so the viewer reads commands
+/// from standard input, each followed by Enter:
+///
+/// - `p` pauses or resumes
+/// - `s` takes a single step while paused
+/// - `+` makes the search faster
+/// - `-` makes the search slower
+///
+/// Use it with `BackTrackSolver::solve_with_hooks`.
+/// This requires the `viewer` feature.
+pub struct Viewer<P> {
+    /// The delay after each step, in milliseconds.
+    pub delay_ms: u64,
+    /// Whether the search is paused.
+    pub paused: bool,
+    guesses: u64,
+    backtracks: u64,
+    depth: usize,
+    /// The guessed positions that lead to the current state.
+    path: Vec<P>,
+    /// The cell that changed last, and whether it was guessed or undone.
+    highlight: Option<([usize; 2], bool)>,
+    start: Option<Instant>,
+    last_draw: Option<Instant>,
+    keys: Option<Receiver<char>>,
+}
+
+impl<P> Default for Viewer<P> {
+    fn default() -> Viewer<P> {
+        Viewer::new()
+    }
+}
+
+impl<P> Viewer<P> {
+    /// Creates a new viewer with a delay of 100 milliseconds.
+    pub fn new() -> Viewer<P> {
+        Viewer {
+            delay_ms: 100,
+            paused: false,
+            guesses: 0,
+            backtracks: 0,
+            depth: 0,
+            path: vec![],
+            highlight: None,
+            start: None,
+            last_draw: None,
+            keys: None,
+        }
+    }
+
+    /// Sets the delay after each step, in milliseconds.
+    pub fn set_delay_ms(&mut self, val: u64) {
+        self.delay_ms = val;
+    }
+
+    /// The delay after each step, in milliseconds.
+    ///
+    /// With no delay, the viewer redraws at most 30 times per second.
+    pub fn delay_ms(mut self, val: u64) -> Self {
+        self.set_delay_ms(val);
+        self
+    }
+
+    /// Sets whether the search is paused.
+    pub fn set_paused(&mut self, val: bool) {
+        self.paused = val;
+    }
+
+    /// Whether the search starts paused.
+    pub fn paused(mut self, val: bool) -> Self {
+        self.set_paused(val);
+        self
+    }
+
+    /// Handles a command, returning `true` if the search should take a step.
+    fn command(&mut self, key: char) -> bool {
+        match key {
+            'p' => {
+                self.paused = !self.paused;
+                !self.paused
+            }
+            's' => true,
+            '+' => {
+                self.delay_ms /= 2;
+                false
+            }
+            '-' => {
+                self.delay_ms = (self.delay_ms * 2).max(1);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads commands that were entered since last time.
+    fn poll(&mut self) {
+        let keys = self.keys.get_or_insert_with(spawn_input);
+        let mut received = vec![];
+        loop {
+            match keys.try_recv() {
+                Ok(key) => received.push(key),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    // There is no input, so pausing would wait forever.
+                    self.paused = false;
+                    break;
+                }
+            }
+        }
+        for key in received {
+            self.command(key);
+        }
+    }
+
+    /// Waits for a command that takes a step.
+    fn wait<T>(&mut self, state: &T) where T: Grid2D<Pos = P> {
+        while self.paused {
+            let key = match self.keys.as_ref().map(|keys| keys.recv()) {
+                Some(Ok(key)) => key,
+                _ => {
+                    self.paused = false;
+                    break;
+                }
+            };
+            if self.command(key) {break};
+            self.draw(state, "");
+        }
+    }
+
+    fn update<T>(&mut self, state: &T) where T: Grid2D<Pos = P> {
+        let now = Instant::now();
+        self.poll();
+        if self.paused || self.delay_ms > 0 || self.last_draw.is_none_or(|t| now - t >= FRAME) {
+            self.draw(state, "");
+        }
+        if self.paused {
+            self.wait(state);
+        } else if self.delay_ms > 0 {
+            thread::sleep(Duration::from_millis(self.delay_ms));
+        }
+    }
+
+    fn draw<T>(&mut self, state: &T, status: &str) where T: Grid2D<Pos = P> {
+        let now = Instant::now();
+        let start = *self.start.get_or_insert(now);
+        let mut out = String::new();
+        if self.last_draw.is_none() {
+            // Clear the screen.
+            out.push_str("\x1b[2J");
+        }
+        self.last_draw = Some(now);
+        out.push_str("\x1b[H");
+        let [w, h] = state.grid_size();
+        for y in 0..h {
+            for x in 0..w {
+                let c = state.cell([x, y]);
+                match self.highlight {
+                    Some((pos, true)) if pos == [x, y] => out.push_str(&format!("\x1b[42m{}\x1b[0m ", c)),
+                    Some((pos, false)) if pos == [x, y] => out.push_str(&format!("\x1b[41m{}\x1b[0m ", c)),
+                    _ => out.push_str(&format!("{} ", c)),
+                }
+            }
+            out.push_str("\x1b[K\n");
+        }
+        let secs = (now - start).as_secs_f64();
+        let rate = if secs > 0.0 {self.guesses as f64 / secs} else {0.0};
+        out.push_str(&format!("depth: {} guesses: {} ({:.0}/s) backtracks: {} {}\x1b[K\n",
+            self.depth, self.guesses, rate, self.backtracks,
+            if self.paused {"[paused]"} else {status}));
+        out.push_str(&format!("delay: {} ms  keys + Enter: p pause, s step, + faster, - slower\x1b[K\n\x1b[J",
+            self.delay_ms));
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        let _ = stdout.write_all(out.as_bytes());
+        let _ = stdout.flush();
+    }
+}
+
+/// Reads commands from standard input on a separate thread.
+fn spawn_input() -> Receiver<char> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            for key in line.chars().filter(|c| !c.is_whitespace()) {
+                if sender.send(key).is_err() {return};
+            }
+        }
+    });
+    receiver
+}
+
+impl<T> SearchHooks<T> for Viewer<T::Pos>
+    where T: Grid2D
+{
+    fn guess(&mut self, state: &T, pos: T::Pos, _val: T::Val, depth: usize) {
+        self.guesses += 1;
+        self.depth = depth;
+        self.path.truncate(depth - 1);
+        self.path.push(pos);
+        self.highlight = state.grid_pos(pos).map(|pos| (pos, true));
+        self.update(state);
+    }
+
+    fn backtrack(&mut self, state: &T, depth: usize) {
+        self.backtracks += 1;
+        self.depth = depth;
+        self.highlight = self.path.get(depth).and_then(|&pos| state.grid_pos(pos)).map(|pos| (pos, false));
+        self.path.truncate(depth);
+        self.update(state);
+    }
+
+    fn solved(&mut self, state: &T, _depth: usize) {
+        self.highlight = None;
+        self.draw(state, "solved");
+    }
+}
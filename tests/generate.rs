@@ -0,0 +1,85 @@
+//! Checks puzzles generated from the Sudoku example.
+
+extern crate quickbacktrack;
+
+#[path = "../examples/sudoku.rs"]
+#[allow(dead_code, clippy::all)]
+mod sudoku;
+
+use quickbacktrack::generate::{self, Generator};
+use quickbacktrack::{BackTrackSolver, Puzzle, Rater, SolveSettings, Strategy, Tier};
+
+use sudoku::Sudoku;
+
+const STRATEGY: Strategy<Sudoku> = (Sudoku::find_min_empty, Sudoku::possible);
+
+fn positions() -> Vec<[usize; 2]> {
+    (0..81).map(|i| [i % 9, i / 9]).collect()
+}
+
+fn solved(seed: u64) -> Sudoku {
+    BackTrackSolver::new(Sudoku {slots: [[0; 9]; 9]}, SolveSettings::new())
+        .random_solution(seed, Sudoku::find_min_empty, Sudoku::possible)
+        .expect("Expected solution").puzzle
+}
+
+/// Counts the solutions of a puzzle, up to a limit.
+fn count_solutions(puzzle: &Sudoku, limit: usize) -> usize {
+    let mut count = 0;
+    let mut stack = vec![puzzle.clone()];
+    while let Some(state) = stack.pop() {
+        match state.find_min_empty() {
+            None if state.is_solved() => {
+                count += 1;
+                if count >= limit {break};
+            }
+            None => {}
+            Some(pos) => for val in state.possible(pos) {
+                let mut next = state.clone();
+                next.set(pos, val);
+                stack.push(next);
+            }
+        }
+    }
+    count
+}
+
+fn tier(puzzle: &Sudoku) -> Tier {
+    Rater::new(SolveSettings::new()).rate(puzzle, &[STRATEGY]).expect("Expected solution").tier
+}
+
+#[test]
+fn unique_and_symmetric() {
+    let solved = solved(3);
+    for seed in 0..3 {
+        let puzzle = Generator::new(SolveSettings::new())
+            .seed(seed)
+            .symmetry(generate::rotational([9, 9]))
+            .generate(&solved, &positions(), 0, STRATEGY);
+        assert_eq!(count_solutions(&puzzle, 2), 1);
+        for pos in positions() {
+            let given = puzzle.get(pos);
+            assert!(given == 0 || given == solved.get(pos));
+            let [x, y] = pos;
+            assert_eq!(given == 0, puzzle.get([8 - x, 8 - y]) == 0);
+        }
+    }
+}
+
+#[test]
+fn target_tier() {
+    let solved = solved(7);
+    let generate = |target, seed| Generator::new(SolveSettings::new())
+        .seed(seed)
+        .target(Rater::new(SolveSettings::new()), target)
+        .generate(&solved, &positions(), 0, STRATEGY);
+    for target in [Tier::Easy, Tier::Medium, Tier::Hard] {
+        for seed in 0..3 {
+            let puzzle = generate(target, seed);
+            assert_eq!(count_solutions(&puzzle, 2), 1);
+            assert_eq!(tier(&puzzle), target);
+        }
+    }
+    // The tier is a ceiling, and the generator runs out of removals before reaching it.
+    assert_eq!(tier(&generate(Tier::Expert, 0)), Tier::Hard);
+}
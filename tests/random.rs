@@ -0,0 +1,53 @@
+//! Checks the random streams of `BackTrackSolver::random_solution_ties`.
+
+extern crate quickbacktrack;
+
+use quickbacktrack::propagate::Domain;
+use quickbacktrack::{BackTrackSolver, BitDomains, Domains, Puzzle, SolveSettings};
+
+/// Positions with values below `n` and no constraints,
+/// so every value is part of a solution and the solver never backtracks.
+fn state(n: u32) -> BitDomains {
+    BitDomains::new(vec![Domain::range(0, n); 8])
+}
+
+/// Returns the positions in the order they are picked, and the values set there.
+fn guesses<K>(seed: u64, n: u32, key: K) -> Vec<(usize, u32)>
+    where K: FnMut(&BitDomains, usize) -> usize
+{
+    let mut picked = vec![];
+    let g = |state: &BitDomains, pos| {
+        picked.push(pos);
+        state.candidates(pos)
+    };
+    let solution = BackTrackSolver::new(state(n), SolveSettings::new())
+        .random_solution_ties(seed, key, g)
+        .expect("Expected solution");
+    assert!(solution.puzzle.is_solved());
+    picked.iter().map(|&pos| (pos, Puzzle::get(&solution.puzzle, pos).unwrap())).collect()
+}
+
+#[test]
+fn same_seed() {
+    for seed in 0..10 {
+        assert_eq!(guesses(seed, 4, |_, _| 0), guesses(seed, 4, |_, _| 0));
+    }
+    let all: Vec<_> = (0..10).map(|seed| guesses(seed, 4, |_, _| 0)).collect();
+    assert!(all.iter().any(|g| *g != all[0]));
+}
+
+#[test]
+fn independent_streams() {
+    let positions = |g: Vec<(usize, u32)>| g.into_iter().map(|(pos, _)| pos).collect::<Vec<_>>();
+    let values = |g: Vec<(usize, u32)>| g.into_iter().map(|(_, val)| val).collect::<Vec<_>>();
+    for seed in 0..10 {
+        // Shuffling more values does not change the tie breaking.
+        let ties = positions(guesses(seed, 4, |_, _| 0));
+        assert_ne!(ties, (0..8).collect::<Vec<_>>());
+        assert_eq!(positions(guesses(seed, 2, |_, _| 0)), ties);
+        assert_eq!(positions(guesses(seed, 9, |_, _| 0)), ties);
+
+        // Breaking ties does not change the value order.
+        assert_eq!(values(guesses(seed, 4, |_, _| 0)), values(guesses(seed, 4, |_, pos| pos)));
+    }
+}
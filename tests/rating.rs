@@ -0,0 +1,94 @@
+//! Calibrates the default weights of `Rater` with Sudoku puzzles.
+//!
+//! The puzzles of the Sudoku example are rated by how the solver handles them,
+//! so the tiers are also checked against published puzzles of well known difficulty.
+
+extern crate quickbacktrack;
+
+#[path = "../examples/sudoku.rs"]
+#[allow(dead_code, clippy::all)]
+mod sudoku;
+
+use quickbacktrack::{Rater, Rating, SolveSettings, Strategy, Tier};
+
+use sudoku::Sudoku;
+
+fn rating(puzzle: &Sudoku) -> Rating {
+    let strategies: Vec<Strategy<Sudoku>> = vec![
+        (Sudoku::find_min_empty, Sudoku::possible),
+        (Sudoku::find_min_potential, Sudoku::possible),
+    ];
+    let rater = Rater::new(SolveSettings::new());
+    rater.rate(puzzle, &strategies).expect("Expected solution")
+}
+
+fn rate(puzzle: Sudoku) -> Tier {
+    rating(&puzzle).tier
+}
+
+/// Reads a puzzle from 81 characters, with `.` for empty slots.
+fn parse(text: &str) -> Sudoku {
+    let mut slots = [[0; 9]; 9];
+    for (i, c) in text.chars().enumerate() {
+        slots[i / 9][i % 9] = c.to_digit(10).unwrap_or(0) as u8;
+    }
+    Sudoku {slots}
+}
+
+/// The example puzzle from the Wikipedia article on Sudoku, solved by singles alone.
+fn wikipedia() -> Sudoku {
+    parse("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79")
+}
+
+/// The first grid of Project Euler problem 96, solved by constraint propagation alone.
+fn euler_96_grid_01() -> Sudoku {
+    parse("..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..")
+}
+
+/// "AI Escargot" by Arto Inkala (2006), published as the most difficult Sudoku at the time.
+fn ai_escargot() -> Sudoku {
+    parse("1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..")
+}
+
+/// The puzzle by Arto Inkala (2012) that was reported as the world's hardest Sudoku.
+fn inkala_2012() -> Sudoku {
+    parse("8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..")
+}
+
+#[test]
+fn easy() {
+    assert_eq!(rate(sudoku::example1()), Tier::Easy);
+}
+
+#[test]
+fn medium() {
+    assert_eq!(rate(sudoku::example6()), Tier::Medium);
+    assert_eq!(rate(sudoku::example8()), Tier::Medium);
+}
+
+#[test]
+fn hard() {
+    assert_eq!(rate(sudoku::example3()), Tier::Hard);
+    assert_eq!(rate(sudoku::example5()), Tier::Hard);
+}
+
+#[test]
+fn expert() {
+    assert_eq!(rate(sudoku::example4()), Tier::Expert);
+    assert_eq!(rate(sudoku::example10()), Tier::Expert);
+}
+
+#[test]
+fn published() {
+    // Puzzles solved by singles need no guesses.
+    for puzzle in [wikipedia(), euler_96_grid_01()] {
+        let rating = rating(&puzzle);
+        assert_eq!(rating.measurements.guesses, 0);
+        assert_eq!(rating.tier, Tier::Easy);
+    }
+    let escargot = rating(&ai_escargot());
+    let inkala = rating(&inkala_2012());
+    assert_eq!(escargot.tier, Tier::Expert);
+    assert_eq!(inkala.tier, Tier::Expert);
+    assert!(inkala.score > escargot.score);
+}
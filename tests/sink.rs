@@ -0,0 +1,88 @@
+//! Checks that debug output goes to the sink instead of the terminal.
+
+extern crate quickbacktrack;
+
+use std::env;
+use std::fmt;
+use std::process::Command;
+use std::sync::mpsc;
+
+use quickbacktrack::{BackTrackSolver, MultiBackTrackSolver, Puzzle, SolveSettings};
+
+/// Set when the test runs as a child process, such that its output can be inspected.
+const CHILD: &str = "QUICKBACKTRACK_SINK_CHILD";
+
+/// Two cells that must add up to 4, with values from 1 to 3.
+#[derive(Clone)]
+struct Pair([u8; 2]);
+
+impl Puzzle for Pair {
+    type Pos = usize;
+    type Val = u8;
+
+    fn set(&mut self, pos: usize, val: u8) {self.0[pos] = val}
+    fn get(&self, pos: usize) -> u8 {self.0[pos]}
+    fn print(&self) {println!("print {:?}", self.0)}
+    fn is_solved(&self) -> bool {self.0[0] + self.0[1] == 4 && self.0[1] != 0}
+    fn remove(&mut self, _other: &Pair) {}
+    fn write_to(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(w, "state {:?}", self.0)
+    }
+}
+
+impl Pair {
+    fn first_empty(&self) -> Option<usize> {self.0.iter().position(|&v| v == 0)}
+    fn values(&self, pos: usize) -> Vec<u8> {
+        if pos == 1 {vec![3, 2, 1]} else {vec![1, 3]}
+    }
+}
+
+#[test]
+fn sink_child() {
+    if env::var_os(CHILD).is_none() {return};
+
+    let (sender, receiver) = mpsc::channel();
+    let mut count = 0;
+    let settings = SolveSettings::new().debug(true).sink(move |msg: &str| {
+        // The sink can change its own state.
+        count += 1;
+        sender.send(format!("{}: {}", count, msg)).unwrap();
+    });
+    let solver = BackTrackSolver::new(Pair([0; 2]), settings);
+    assert!(solver.solve(Pair::first_empty, Pair::values).is_some());
+    let lines: Vec<String> = receiver.try_iter().collect();
+    assert_eq!(lines, vec![
+        "1: state [0, 0]\n",
+        "2: Guess 0, 3 depth ch: 1 prev: 1 it: 1",
+        "3: state [3, 0]\n",
+        "4: Guess 1, 1 depth ch: 2 prev: 2 it: 2",
+        "5: state [3, 1]\n",
+        "6: Solved! Iterations: 3",
+    ]);
+
+    let (sender, receiver) = mpsc::channel();
+    let settings = SolveSettings::new().debug(true).sink(move |msg: &str| sender.send(msg.to_string()).unwrap());
+    let solver = MultiBackTrackSolver::new(settings);
+    let strategies: &[(fn(&_) -> _, fn(&_, _) -> _)] = &[(Pair::first_empty, Pair::values)];
+    assert!(solver.solve(Pair([0; 2]), strategies).is_some());
+    let lines: Vec<String> = receiver.try_iter().collect();
+    assert!(lines.iter().any(|line| line == "Strategy 0"));
+}
+
+#[test]
+fn debug_output_only_reaches_sink() {
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "sink_child", "--nocapture", "--test-threads", "1"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}{}", stdout, stderr);
+    assert!(stdout.contains("1 passed"), "{}", stdout);
+    for text in [&stdout, &stderr] {
+        for debug in ["Solved!", "Strategy", "state", "print"] {
+            assert!(!text.contains(debug), "`{}` in terminal output:\n{}", debug, text);
+        }
+    }
+}